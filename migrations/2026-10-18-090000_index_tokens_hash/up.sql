@@ -0,0 +1 @@
+CREATE UNIQUE INDEX tokens_token_hash_idx ON tokens (token_hash);
@@ -0,0 +1,9 @@
+-- Failed login counters, `scope` is either `account` (subject = username) or `ip` (subject = address)
+CREATE TABLE login_throttles (
+    scope VARCHAR NOT NULL,
+    subject TEXT NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    locked_until TIMESTAMPTZ,
+    PRIMARY KEY (scope, subject)
+);
@@ -0,0 +1,24 @@
+-- Usernames and emails are unique regardless of case. Existing duplicates have to be
+-- resolved by hand first, the migration lists them instead of failing on the index.
+DO $$
+DECLARE
+    duplicates TEXT;
+BEGIN
+    SELECT string_agg(format('%s %L (%s rows)', field, value, total), E'\n')
+    INTO duplicates
+    FROM (
+        SELECT 'username' AS field, lower(username) AS value, count(*) AS total
+        FROM users GROUP BY lower(username) HAVING count(*) > 1
+        UNION ALL
+        SELECT 'email' AS field, lower(email) AS value, count(*) AS total
+        FROM users GROUP BY lower(email) HAVING count(*) > 1
+    ) AS conflicts;
+
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'Cannot enforce unique users, resolve these duplicates first:%', E'\n' || duplicates;
+    END IF;
+END
+$$;
+
+CREATE UNIQUE INDEX users_username_lower_key ON users (lower(username));
+CREATE UNIQUE INDEX users_email_lower_key ON users (lower(email));
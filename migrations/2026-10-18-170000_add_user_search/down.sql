@@ -0,0 +1,3 @@
+DROP INDEX users_email_trgm_idx;
+DROP INDEX users_username_trgm_idx;
+-- pg_trgm stays installed, other objects may depend on it
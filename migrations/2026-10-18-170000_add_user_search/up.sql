@@ -0,0 +1,6 @@
+-- Trigram indexes behind the fuzzy user search. pg_trgm ignores case on its own, so the
+-- plain columns are indexed rather than lower() like the unique indexes.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX users_username_trgm_idx ON users USING gin (username gin_trgm_ops);
+CREATE INDEX users_email_trgm_idx ON users USING gin (email gin_trgm_ops);
@@ -0,0 +1,2 @@
+-- Nothing to undo, the flag and the role simply stay in step
+SELECT 1;
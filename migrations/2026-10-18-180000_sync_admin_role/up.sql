@@ -0,0 +1,12 @@
+-- The admin role becomes the only source of administrator rights. Both used to grant
+-- every permission, so users holding either one keep their rights by getting both.
+INSERT INTO user_roles (user_id, role_id)
+SELECT u.id, r.id FROM users u CROSS JOIN roles r
+WHERE u.is_admin AND r.name = 'admin'
+ON CONFLICT DO NOTHING;
+
+UPDATE users SET is_admin = true
+WHERE NOT is_admin AND id IN (
+    SELECT ur.user_id FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+    WHERE r.name = 'admin'
+);
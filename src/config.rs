@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::env;
 
+use argon2::{Algorithm, Params};
 use dotenvy::dotenv;
 use tokio::sync::OnceCell;
 
@@ -7,17 +9,87 @@ use tokio::sync::OnceCell;
 struct ServerConfig {
     host: String,
     port: u16,
+    metrics_host: String,
+    metrics_port: u16,
 }
 
 #[derive(Debug)]
 struct DatabaseConfig {
     url: String,
+    pool_wait_timeout_seconds: u64,
+}
+
+#[derive(Debug)]
+struct AuthConfig {
+    token_ttl_minutes: i64,
+    access_token_ttl_minutes: i64,
+    jwt_keys: HashMap<String, String>,
+    jwt_active_kid: String,
+    password_reset_ttl_minutes: i64,
+    require_email_verification: bool,
+    email_verification_ttl_hours: i64,
+    email_verification_resend_cooldown_seconds: i64,
+}
+
+#[derive(Debug)]
+struct MailerConfig {
+    backend: String,
+    file_path: String,
+    public_url: String,
+    frontend_url: String,
+}
+
+#[derive(Debug)]
+struct MfaConfig {
+    encryption_key: [u8; 32],
+    issuer: String,
+    challenge_ttl_minutes: i64,
+    challenge_max_attempts: i32,
+}
+
+#[derive(Debug)]
+struct LoginThrottleConfig {
+    max_failed_attempts: i32,
+    ip_max_failed_attempts: i32,
+    failure_window_minutes: i64,
+    lockout_minutes: i64,
+    backoff_base_seconds: i64,
+    backoff_max_seconds: i64,
+}
+
+#[derive(Debug)]
+struct PasswordPolicyConfig {
+    min_length: usize,
+    min_character_classes: usize,
+    min_strength_score: u8,
+    breached_list_path: Option<String>,
+}
+
+#[derive(Debug)]
+struct PasswordHashingConfig {
+    algorithm: Algorithm,
+    params: Params,
+    pepper: Option<String>,
+    workers: usize,
+    queue_limit: usize,
+}
+
+#[derive(Debug)]
+struct UserSearchConfig {
+    min_similarity: f32,
 }
 
 #[derive(Debug)]
 pub struct Config {
     server: ServerConfig,
     db: DatabaseConfig,
+    auth: AuthConfig,
+    mailer: MailerConfig,
+    mfa: MfaConfig,
+    login_throttle: LoginThrottleConfig,
+    password_policy: PasswordPolicyConfig,
+    password_hashing: PasswordHashingConfig,
+    user_search: UserSearchConfig,
 }
 
 impl Config {
@@ -25,6 +97,11 @@ impl Config {
         &self.db.url
     }
 
+    // How long a request waits for a free database connection before failing with a 503
+    pub fn db_pool_wait_timeout_seconds(&self) -> u64 {
+        self.db.pool_wait_timeout_seconds
+    }
+
     pub fn server_host(&self) -> &str {
         &self.server.host
     }
@@ -32,6 +109,162 @@ impl Config {
     pub fn server_port(&self) -> u16 {
         self.server.port
     }
+
+    // Separate listener for the Prometheus metrics, kept off the public API
+    pub fn metrics_host(&self) -> &str {
+        &self.server.metrics_host
+    }
+
+    pub fn metrics_port(&self) -> u16 {
+        self.server.metrics_port
+    }
+
+    pub fn token_ttl_minutes(&self) -> i64 {
+        self.auth.token_ttl_minutes
+    }
+
+    pub fn access_token_ttl_minutes(&self) -> i64 {
+        self.auth.access_token_ttl_minutes
+    }
+
+    // Returns the kid and secret used to sign new access tokens
+    pub fn jwt_active_key(&self) -> (&str, &str) {
+        let kid = self.auth.jwt_active_kid.as_str();
+        (kid, self.auth.jwt_keys[kid].as_str())
+    }
+
+    pub fn password_reset_ttl_minutes(&self) -> i64 {
+        self.auth.password_reset_ttl_minutes
+    }
+
+    // When enabled, accounts cannot log in until their email is verified
+    pub fn require_email_verification(&self) -> bool {
+        self.auth.require_email_verification
+    }
+
+    pub fn email_verification_ttl_hours(&self) -> i64 {
+        self.auth.email_verification_ttl_hours
+    }
+
+    pub fn email_verification_resend_cooldown_seconds(&self) -> i64 {
+        self.auth.email_verification_resend_cooldown_seconds
+    }
+
+    pub fn mailer(&self) -> &str {
+        &self.mailer.backend
+    }
+
+    pub fn mailer_file_path(&self) -> &str {
+        &self.mailer.file_path
+    }
+
+    // Base URL of this API, used to build the links sent by email that it serves itself
+    pub fn public_url(&self) -> &str {
+        &self.mailer.public_url
+    }
+
+    // Base URL of the web application, for emailed links that need a page to fill in
+    pub fn frontend_url(&self) -> &str {
+        &self.mailer.frontend_url
+    }
+
+    // AES-256 key protecting the TOTP secrets stored in the database
+    pub fn mfa_encryption_key(&self) -> &[u8; 32] {
+        &self.mfa.encryption_key
+    }
+
+    // Issuer shown by authenticator apps next to the account name
+    pub fn mfa_issuer(&self) -> &str {
+        &self.mfa.issuer
+    }
+
+    pub fn mfa_challenge_ttl_minutes(&self) -> i64 {
+        self.mfa.challenge_ttl_minutes
+    }
+
+    pub fn mfa_challenge_max_attempts(&self) -> i32 {
+        self.mfa.challenge_max_attempts
+    }
+
+    // Failed logins on one account before it is temporarily locked
+    pub fn login_max_failed_attempts(&self) -> i32 {
+        self.login_throttle.max_failed_attempts
+    }
+
+    // Failed logins from one IP address, across accounts, before it is throttled
+    pub fn login_ip_max_failed_attempts(&self) -> i32 {
+        self.login_throttle.ip_max_failed_attempts
+    }
+
+    // Failures older than this are forgotten
+    pub fn login_failure_window_minutes(&self) -> i64 {
+        self.login_throttle.failure_window_minutes
+    }
+
+    pub fn login_lockout_minutes(&self) -> i64 {
+        self.login_throttle.lockout_minutes
+    }
+
+    pub fn login_backoff_base_seconds(&self) -> i64 {
+        self.login_throttle.backoff_base_seconds
+    }
+
+    pub fn login_backoff_max_seconds(&self) -> i64 {
+        self.login_throttle.backoff_max_seconds
+    }
+
+    pub fn password_min_length(&self) -> usize {
+        self.password_policy.min_length
+    }
+
+    // Among lowercase letters, uppercase letters, digits and symbols
+    pub fn password_min_character_classes(&self) -> usize {
+        self.password_policy.min_character_classes
+    }
+
+    // From 0 (trivially guessable) to 4 (very hard to guess)
+    pub fn password_min_strength_score(&self) -> u8 {
+        self.password_policy.min_strength_score
+    }
+
+    // File of breached password hashes, no breach check when unset
+    pub fn password_breached_list_path(&self) -> Option<&str> {
+        self.password_policy.breached_list_path.as_deref()
+    }
+
+    pub fn password_hash_algorithm(&self) -> Algorithm {
+        self.password_hashing.algorithm
+    }
+
+    // Memory, iterations and parallelism used for new hashes
+    pub fn password_hash_params(&self) -> &Params {
+        &self.password_hashing.params
+    }
+
+    // Server-side secret mixed into every new hash, never stored in the database
+    pub fn password_pepper(&self) -> Option<&[u8]> {
+        self.password_hashing.pepper.as_deref().map(str::as_bytes)
+    }
+
+    // Threads dedicated to hashing and verifying passwords
+    pub fn password_hash_workers(&self) -> usize {
+        self.password_hashing.workers
+    }
+
+    // Jobs allowed to wait for a hashing thread before requests are refused with a 503
+    pub fn password_hash_queue_limit(&self) -> usize {
+        self.password_hashing.queue_limit
+    }
+
+    // Word similarity, from 0 to 1, a user needs to show up in search results
+    pub fn user_search_min_similarity(&self) -> f32 {
+        self.user_search.min_similarity
+    }
+
+    // Looks up a verification secret by kid, retired keys stay valid until removed
+    pub fn jwt_key(&self, kid: &str) -> Option<&str> {
+        self.auth.jwt_keys.get(kid).map(String::as_str)
+    }
 }
 
 pub static CONFIG: OnceCell<Config> = OnceCell::const_new();
@@ -44,15 +277,201 @@ async fn init_config() -> Config {
             .unwrap_or_else(|_| String::from("3000"))
             .parse::<u16>()
             .unwrap(),
+        metrics_host: env::var("METRICS_HOST").unwrap_or_else(|_| String::from("127.0.0.1")),
+        metrics_port: env::var("METRICS_PORT")
+            .unwrap_or_else(|_| String::from("9464"))
+            .parse::<u16>()
+            .unwrap(),
     };
 
     let database_config = DatabaseConfig {
         url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+        pool_wait_timeout_seconds: env::var("DB_POOL_WAIT_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| String::from("5"))
+            .parse::<u64>()
+            .unwrap(),
+    };
+
+    // Format: `kid1:secret1,kid2:secret2`, new tokens are signed with JWT_ACTIVE_KID or,
+    // when unset, with the first key listed
+    let jwt_key_list: Vec<(String, String)> = env::var("JWT_SIGNING_KEYS")
+        .expect("JWT_SIGNING_KEYS must be set")
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (kid, secret) = entry
+                .split_once(':')
+                .expect("JWT_SIGNING_KEYS entries must be kid:secret pairs");
+            let (kid, secret) = (kid.trim(), secret.trim());
+            assert!(
+                !kid.is_empty() && !secret.is_empty(),
+                "JWT_SIGNING_KEYS entries must have a non-empty kid and secret"
+            );
+            (kid.to_string(), secret.to_string())
+        })
+        .collect();
+
+    let jwt_active_kid = env::var("JWT_ACTIVE_KID").unwrap_or_else(|_| {
+        jwt_key_list
+            .first()
+            .map(|(kid, _)| kid.clone())
+            .expect("JWT_SIGNING_KEYS must contain at least one kid:secret pair")
+    });
+    let jwt_keys: HashMap<String, String> = jwt_key_list.into_iter().collect();
+    assert!(
+        jwt_keys.contains_key(&jwt_active_kid),
+        "JWT_ACTIVE_KID must reference a key of JWT_SIGNING_KEYS"
+    );
+
+    let auth_config = AuthConfig {
+        token_ttl_minutes: env::var("TOKEN_TTL_MINUTES")
+            .unwrap_or_else(|_| String::from("1440"))
+            .parse::<i64>()
+            .unwrap(),
+        access_token_ttl_minutes: env::var("ACCESS_TOKEN_TTL_MINUTES")
+            .unwrap_or_else(|_| String::from("15"))
+            .parse::<i64>()
+            .unwrap(),
+        jwt_keys,
+        jwt_active_kid,
+        password_reset_ttl_minutes: env::var("PASSWORD_RESET_TTL_MINUTES")
+            .unwrap_or_else(|_| String::from("30"))
+            .parse::<i64>()
+            .unwrap(),
+        require_email_verification: env::var("REQUIRE_EMAIL_VERIFICATION")
+            .unwrap_or_else(|_| String::from("false"))
+            .parse::<bool>()
+            .unwrap(),
+        email_verification_ttl_hours: env::var("EMAIL_VERIFICATION_TTL_HOURS")
+            .unwrap_or_else(|_| String::from("48"))
+            .parse::<i64>()
+            .unwrap(),
+        email_verification_resend_cooldown_seconds: env::var("EMAIL_VERIFICATION_RESEND_COOLDOWN_SECONDS")
+            .unwrap_or_else(|_| String::from("60"))
+            .parse::<i64>()
+            .unwrap(),
+    };
+
+    let mailer_config = MailerConfig {
+        backend: env::var("MAILER").unwrap_or_else(|_| String::from("log")),
+        file_path: env::var("MAILER_FILE_PATH").unwrap_or_else(|_| String::from("mail.log")),
+        public_url: env::var("PUBLIC_URL").unwrap_or_else(|_| String::from("http://localhost:3000")),
+        frontend_url: env::var("FRONTEND_URL").unwrap_or_else(|_| String::from("http://localhost:5173")),
+    };
+
+    // 32 bytes, hex encoded
+    let mfa_encryption_key: [u8; 32] = hex::decode(
+        env::var("MFA_ENCRYPTION_KEY").expect("MFA_ENCRYPTION_KEY must be set"),
+    )
+    .expect("MFA_ENCRYPTION_KEY must be hex encoded")
+    .try_into()
+    .expect("MFA_ENCRYPTION_KEY must be 32 bytes long");
+
+    let mfa_config = MfaConfig {
+        encryption_key: mfa_encryption_key,
+        issuer: env::var("MFA_ISSUER").unwrap_or_else(|_| String::from("Nanomon")),
+        challenge_ttl_minutes: env::var("MFA_CHALLENGE_TTL_MINUTES")
+            .unwrap_or_else(|_| String::from("5"))
+            .parse::<i64>()
+            .unwrap(),
+        challenge_max_attempts: env::var("MFA_CHALLENGE_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| String::from("5"))
+            .parse::<i32>()
+            .unwrap(),
+    };
+
+    let login_throttle_config = LoginThrottleConfig {
+        max_failed_attempts: env::var("LOGIN_MAX_FAILED_ATTEMPTS")
+            .unwrap_or_else(|_| String::from("5"))
+            .parse::<i32>()
+            .unwrap(),
+        ip_max_failed_attempts: env::var("LOGIN_IP_MAX_FAILED_ATTEMPTS")
+            .unwrap_or_else(|_| String::from("50"))
+            .parse::<i32>()
+            .unwrap(),
+        failure_window_minutes: env::var("LOGIN_FAILURE_WINDOW_MINUTES")
+            .unwrap_or_else(|_| String::from("15"))
+            .parse::<i64>()
+            .unwrap(),
+        lockout_minutes: env::var("LOGIN_LOCKOUT_MINUTES")
+            .unwrap_or_else(|_| String::from("15"))
+            .parse::<i64>()
+            .unwrap(),
+        backoff_base_seconds: env::var("LOGIN_BACKOFF_BASE_SECONDS")
+            .unwrap_or_else(|_| String::from("1"))
+            .parse::<i64>()
+            .unwrap(),
+        backoff_max_seconds: env::var("LOGIN_BACKOFF_MAX_SECONDS")
+            .unwrap_or_else(|_| String::from("30"))
+            .parse::<i64>()
+            .unwrap(),
+    };
+
+    let password_policy_config = PasswordPolicyConfig {
+        min_length: env::var("PASSWORD_MIN_LENGTH")
+            .unwrap_or_else(|_| String::from("8"))
+            .parse::<usize>()
+            .unwrap(),
+        min_character_classes: env::var("PASSWORD_MIN_CHARACTER_CLASSES")
+            .unwrap_or_else(|_| String::from("2"))
+            .parse::<usize>()
+            .unwrap(),
+        min_strength_score: env::var("PASSWORD_MIN_STRENGTH_SCORE")
+            .unwrap_or_else(|_| String::from("2"))
+            .parse::<u8>()
+            .unwrap(),
+        breached_list_path: env::var("PASSWORD_BREACHED_LIST_PATH").ok(),
+    };
+
+    let password_hashing_config = PasswordHashingConfig {
+        algorithm: env::var("PASSWORD_HASH_ALGORITHM")
+            .unwrap_or_else(|_| String::from("argon2id"))
+            .parse::<Algorithm>()
+            .expect("PASSWORD_HASH_ALGORITHM must be argon2id, argon2i or argon2d"),
+        params: Params::new(
+            env::var("PASSWORD_HASH_M_COST")
+                .unwrap_or_else(|_| Params::DEFAULT_M_COST.to_string())
+                .parse::<u32>()
+                .unwrap(),
+            env::var("PASSWORD_HASH_T_COST")
+                .unwrap_or_else(|_| Params::DEFAULT_T_COST.to_string())
+                .parse::<u32>()
+                .unwrap(),
+            env::var("PASSWORD_HASH_P_COST")
+                .unwrap_or_else(|_| Params::DEFAULT_P_COST.to_string())
+                .parse::<u32>()
+                .unwrap(),
+            None,
+        )
+        .expect("PASSWORD_HASH_M_COST, PASSWORD_HASH_T_COST and PASSWORD_HASH_P_COST must be valid Argon2 parameters"),
+        pepper: env::var("PASSWORD_PEPPER").ok().filter(|pepper| !pepper.is_empty()),
+        workers: env::var("PASSWORD_HASH_WORKERS")
+            .ok()
+            .map(|workers| workers.parse::<usize>().unwrap())
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from)),
+        queue_limit: env::var("PASSWORD_HASH_QUEUE_LIMIT")
+            .unwrap_or_else(|_| String::from("64"))
+            .parse::<usize>()
+            .unwrap(),
+    };
+
+    let user_search_config = UserSearchConfig {
+        min_similarity: env::var("USER_SEARCH_MIN_SIMILARITY")
+            .unwrap_or_else(|_| String::from("0.4"))
+            .parse::<f32>()
+            .unwrap(),
     };
 
     Config {
         server: server_config,
         db: database_config,
+        auth: auth_config,
+        mailer: mailer_config,
+        mfa: mfa_config,
+        login_throttle: login_throttle_config,
+        password_policy: password_policy_config,
+        password_hashing: password_hashing_config,
+        user_search: user_search_config,
     }
 }
 
@@ -0,0 +1,60 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::errors::Problem;
+use crate::infra::errors::InfraError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiKeyModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub enum ApiKeyError {
+    NotFound(Uuid),
+    UnknownScope(String),
+    ScopeNotGranted(String),
+    Forbidden,
+    InfraError(InfraError),
+}
+
+impl IntoResponse for ApiKeyError {
+    fn into_response(self) -> axum::response::Response {
+        let problem = match self {
+            Self::NotFound(id) => Problem::new(
+                StatusCode::NOT_FOUND,
+                "api_key_not_found",
+                format!("API key with id {} has not been found", id),
+            ),
+            Self::UnknownScope(scope) => Problem::new(
+                StatusCode::BAD_REQUEST,
+                "unknown_scope",
+                format!("Scope {} does not exist", scope),
+            ),
+            Self::ScopeNotGranted(scope) => Problem::new(
+                StatusCode::FORBIDDEN,
+                "scope_not_granted",
+                format!("You do not hold the permission {} and cannot give it to a key", scope),
+            ),
+            Self::Forbidden => Problem::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "You are not allowed to perform this action",
+            ),
+            Self::InfraError(db_error) => Problem::from(db_error),
+        };
+        problem.into_response()
+    }
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+
+pub const ACCOUNT_SCOPE: &str = "account";
+pub const IP_SCOPE: &str = "ip";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoginThrottleModel {
+    pub scope: String,
+    pub subject: String,
+    pub failed_attempts: i32,
+    pub last_failed_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+// TOTP state of a user, the secret stays encrypted until it is needed
+#[derive(Clone, Debug, PartialEq)]
+pub struct TotpModel {
+    pub user_id: Uuid,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
+    pub totp_last_step: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MfaChallengeModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub consumed_at: Option<DateTime<Utc>>,
+}
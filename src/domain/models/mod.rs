@@ -1,2 +1,7 @@
 pub mod user;
 pub mod token;
+pub mod role;
+pub mod api_key;
+pub mod mfa;
+pub mod login_throttle;
+pub mod pagination;
@@ -0,0 +1,85 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::ValidationError;
+
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+// Position right after the last item of a page, for a given sort order. Rows are
+// ordered by the sort field then by id, so the pair identifies a single row.
+//
+// Clients only ever see it as an opaque string (base64url of its JSON form).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cursor {
+    pub sort: String,
+    pub direction: SortDirection,
+    pub value: String,
+    pub id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorFields {
+    sort: String,
+    direction: SortDirection,
+    value: String,
+    id: Uuid,
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = String;
+
+    fn try_from(encoded: String) -> Result<Self, Self::Error> {
+        let invalid = || String::from("invalid pagination cursor");
+        let json = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| invalid())?;
+        let fields: CursorFields = serde_json::from_slice(&json).map_err(|_| invalid())?;
+
+        Ok(Cursor {
+            sort: fields.sort,
+            direction: fields.direction,
+            value: fields.value,
+            id: fields.id,
+        })
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(cursor: Cursor) -> Self {
+        let fields = CursorFields {
+            sort: cursor.sort,
+            direction: cursor.direction,
+            value: cursor.value,
+            id: cursor.id,
+        };
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&fields).unwrap_or_default())
+    }
+}
+
+// A cursor only makes sense for the sort order it was issued for
+pub fn validate_cursor(cursor: &Cursor, sort: &str, direction: SortDirection) -> Result<(), ValidationError> {
+    if cursor.sort == sort && cursor.direction == direction {
+        Ok(())
+    } else {
+        Err(ValidationError::new("cursor_mismatch")
+            .with_message("Cursor was issued for another sort field or direction".into()))
+    }
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // None on the last page
+    pub next_cursor: Option<String>,
+    // Only counted when requested, it costs a second query
+    pub total: Option<i64>,
+}
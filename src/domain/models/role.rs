@@ -0,0 +1,67 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::errors::Problem;
+use crate::infra::errors::InfraError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoleModel {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermissionModel {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug)]
+pub enum RoleError {
+    NotFound(Uuid),
+    UserNotFound(Uuid),
+    UnknownPermission(String),
+    BuiltInRole(String),
+    Forbidden,
+    InfraError(InfraError),
+}
+
+impl IntoResponse for RoleError {
+    fn into_response(self) -> axum::response::Response {
+        let problem = match self {
+            Self::NotFound(id) => Problem::new(
+                StatusCode::NOT_FOUND,
+                "role_not_found",
+                format!("Role with id {} has not been found", id),
+            ),
+            Self::UserNotFound(id) => Problem::new(
+                StatusCode::NOT_FOUND,
+                "user_not_found",
+                format!("User with id {} has not been found", id),
+            ),
+            Self::UnknownPermission(name) => Problem::new(
+                StatusCode::BAD_REQUEST,
+                "unknown_permission",
+                format!("Permission {} does not exist", name),
+            ),
+            Self::BuiltInRole(name) => Problem::new(
+                StatusCode::CONFLICT,
+                "built_in_role",
+                format!("Role {} is built in and cannot be deleted", name),
+            ),
+            Self::Forbidden => Problem::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "You are not allowed to perform this action",
+            ),
+            Self::InfraError(db_error) => Problem::from(db_error),
+        };
+        problem.into_response()
+    }
+}
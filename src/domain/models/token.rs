@@ -1,10 +1,9 @@
 use axum::http::StatusCode;
-use axum::Json;
 use axum::response::IntoResponse;
 use chrono::{DateTime, Utc};
-use serde_json::json;
 use uuid::Uuid;
-use crate::infra::db::schema::tokens::previous_token_id;
+
+use crate::errors::Problem;
 use crate::infra::errors::InfraError;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -23,39 +22,41 @@ pub struct TokenModel {
 
 #[derive(Debug)]
 pub enum TokenError {
-    InternalServerError,
     NotFound(Uuid),
-    InvalidUuid(String),
+    InvalidToken,
+    TokenReused,
+    Forbidden,
+    TokenSigningError(jsonwebtoken::errors::Error),
     InfraError(InfraError),
 }
 
 
 impl IntoResponse for TokenError {
     fn into_response(self) -> axum::response::Response {
-        let (status, err_msg) = match self {
-            Self::NotFound(id) => (
+        let problem = match self {
+            Self::NotFound(id) => Problem::new(
                 StatusCode::NOT_FOUND,
-                format!("TokenModel with id {} has not been found", id),
+                "token_not_found",
+                format!("Token with id {} has not been found", id),
             ),
-            Self::InfraError(db_error) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Internal server error: {}", db_error),
+            Self::InvalidToken => Problem::new(
+                StatusCode::UNAUTHORIZED,
+                "invalid_token",
+                "Token is invalid, expired or revoked",
             ),
-            Self::InvalidUuid(id) => (
-                StatusCode::BAD_REQUEST,
-                format!("Invalid UUID: {}", id),
-                ),
-            _ => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                String::from("Internal server error"),
+            Self::TokenReused => Problem::new(
+                StatusCode::UNAUTHORIZED,
+                "token_reused",
+                "Token has already been used, all related sessions were revoked. Please log in again",
             ),
-        };
-        (
-            status,
-            Json(
-                json!({"resource":"UserModel", "message": err_msg, "happened_at" : chrono::Utc::now() }),
+            Self::Forbidden => Problem::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "You are not allowed to perform this action",
             ),
-        )
-            .into_response()
+            Self::TokenSigningError(err) => Problem::internal(format!("Failed to sign access token: {}", err)),
+            Self::InfraError(db_error) => Problem::from(db_error),
+        };
+        problem.into_response()
     }
 }
@@ -1,11 +1,11 @@
 use axum::http::StatusCode;
 use argon2::password_hash::Error as PasswordHashError;
-use axum::Json;
 use axum::response::IntoResponse;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde_json::json;
 use uuid::Uuid;
 
+use crate::errors::Problem;
 use crate::infra::errors::InfraError;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -16,14 +16,52 @@ pub struct UserModel {
     pub password_hash: String,
     pub is_admin: bool,
     pub created_at: NaiveDate,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
+}
+
+// A user found by the fuzzy search, with how closely it matched from 0 to 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserSearchHit {
+    pub user: UserModel,
+    pub score: f32,
+}
+
+// A rule of the password policy the submitted password breaks
+#[derive(Debug)]
+pub struct PasswordPolicyViolation {
+    pub code: &'static str,
+    pub message: String,
 }
 
 #[derive(Debug)]
 pub enum UserError {
     InternalServerError,
     NotFound(Uuid),
-    InvalidCredentials(String),
+    SessionNotFound(Uuid),
+    InvalidCredentials,
+    // Another user already holds this value of the named field
+    Conflict(&'static str),
+    Unauthorized,
+    Forbidden,
+    WeakPassword(Vec<PasswordPolicyViolation>),
+    InvalidResetToken,
+    InvalidVerificationToken,
+    EmailNotVerified,
+    MfaAlreadyEnabled,
+    MfaNotEnabled,
+    MfaEnrollmentNotStarted,
+    InvalidMfaCode,
+    InvalidMfaChallenge,
+    // Locked until the given time after too many failed logins
+    AccountLocked(DateTime<Utc>),
+    // Seconds to wait before the next attempt
+    TooManyAttempts(i64),
+    // Every password hashing worker is busy and the queue is full
+    HashingUnavailable,
     PasswordHashError(PasswordHashError),
+    TokenSigningError(jsonwebtoken::errors::Error),
+    SecretEncryptionError(aes_gcm::Error),
     InfraError(InfraError),
 }
 
@@ -35,34 +73,117 @@ impl From<PasswordHashError> for UserError {
 
 impl IntoResponse for UserError {
     fn into_response(self) -> axum::response::Response {
-        let (status, err_msg) = match self {
-            Self::NotFound(id) => (
+        let problem = match self {
+            Self::NotFound(id) => Problem::new(
                 StatusCode::NOT_FOUND,
-                format!("UserModel with id {} has not been found", id),
+                "user_not_found",
+                format!("User with id {} has not been found", id),
             ),
-            Self::InvalidCredentials(username) => (
+            Self::SessionNotFound(id) => Problem::new(
+                StatusCode::NOT_FOUND,
+                "session_not_found",
+                format!("Session with id {} has not been found", id),
+            ),
+            Self::InvalidCredentials => Problem::new(
                 StatusCode::UNAUTHORIZED,
-                format!("User with username {} and provided password has not been found", username),
+                "invalid_credentials",
+                "Invalid username or password",
             ),
-            Self::InfraError(db_error) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Internal server error: {}", db_error),
+            Self::Conflict(field) => Problem::new(
+                StatusCode::CONFLICT,
+                "user_conflict",
+                format!("A user with this {} already exists", field),
+            )
+            .with_extension("field", field),
+            Self::Unauthorized => Problem::new(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "Missing, expired or revoked authentication token",
             ),
-            Self::PasswordHashError(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Internal server error: {}", err),
-                ),
-            _ => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                String::from("Internal server error"),
+            Self::Forbidden => Problem::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "You are not allowed to perform this action",
             ),
-        };
-        (
-            status,
-            Json(
-                json!({"resource":"UserModel", "message": err_msg, "happened_at" : chrono::Utc::now() }),
+            // Same shape as payload validation errors, under the `password` field
+            Self::WeakPassword(violations) => Problem::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "weak_password",
+                "Password does not meet the password policy",
+            )
+            .with_extension(
+                "errors",
+                json!({
+                    "password": violations
+                        .iter()
+                        .map(|violation| json!({ "code": violation.code, "message": violation.message }))
+                        .collect::<Vec<_>>(),
+                }),
+            ),
+            Self::InvalidResetToken => Problem::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_reset_token",
+                "Password reset token is invalid, expired or already used",
             ),
-        )
-            .into_response()
+            Self::InvalidVerificationToken => Problem::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_verification_token",
+                "Email verification token is invalid, expired or already used",
+            ),
+            Self::EmailNotVerified => Problem::new(
+                StatusCode::FORBIDDEN,
+                "email_not_verified",
+                "Email address has not been verified yet",
+            ),
+            Self::MfaAlreadyEnabled => Problem::new(
+                StatusCode::CONFLICT,
+                "mfa_already_enabled",
+                "Two-factor authentication is already enabled",
+            ),
+            Self::MfaNotEnabled => Problem::new(
+                StatusCode::BAD_REQUEST,
+                "mfa_not_enabled",
+                "Two-factor authentication is not enabled",
+            ),
+            Self::MfaEnrollmentNotStarted => Problem::new(
+                StatusCode::BAD_REQUEST,
+                "mfa_enrollment_not_started",
+                "No pending two-factor enrollment to confirm",
+            ),
+            Self::InvalidMfaCode => Problem::new(
+                StatusCode::UNAUTHORIZED,
+                "invalid_mfa_code",
+                "Invalid two-factor authentication code",
+            ),
+            Self::InvalidMfaChallenge => Problem::new(
+                StatusCode::UNAUTHORIZED,
+                "invalid_mfa_challenge",
+                "Two-factor challenge is invalid, expired or already used",
+            ),
+            Self::AccountLocked(locked_until) => Problem::new(
+                StatusCode::LOCKED,
+                "account_locked",
+                format!("Account is temporarily locked until {}", locked_until),
+            )
+            .with_retry_after((locked_until - Utc::now()).num_seconds().max(1)),
+            Self::TooManyAttempts(seconds) => Problem::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "too_many_attempts",
+                format!("Too many failed login attempts, retry in {} seconds", seconds),
+            )
+            .with_retry_after(seconds),
+            Self::HashingUnavailable => Problem::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "server_busy",
+                "The server is busy, please retry shortly",
+            )
+            .with_retry_after(1),
+            Self::PasswordHashError(err) => Problem::internal(err),
+            Self::TokenSigningError(err) => Problem::internal(format!("Failed to sign access token: {}", err)),
+            Self::SecretEncryptionError(err) => Problem::internal(format!("Failed to encrypt TOTP secret: {}", err)),
+            Self::InfraError(db_error) => Problem::from(db_error),
+            Self::InternalServerError => Problem::internal("unexpected user error"),
+        };
+        problem.into_response()
     }
 }
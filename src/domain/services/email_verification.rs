@@ -0,0 +1,43 @@
+use chrono::{Duration, Utc};
+
+use crate::config::config;
+use crate::domain::models::user::UserModel;
+use crate::handlers::tokens::{generate_token, hash_token};
+use crate::infra::errors::InfraError;
+use crate::infra::mailer::EmailMessage;
+use crate::infra::repositories::email_verification_repository;
+use crate::infra::repositories::email_verification_repository::NewEmailVerificationTokenDb;
+use crate::AppState;
+
+// Issues a verification token for the user's current email and mails the link.
+// Delivery failures are only logged, the user can always ask for a new email.
+pub async fn send_verification_email(state: &AppState, user: &UserModel) -> Result<(), InfraError> {
+    let app_config = config().await;
+    let token = generate_token();
+
+    let new_token_db = NewEmailVerificationTokenDb {
+        user_id: user.id,
+        token_hash: hash_token(&token),
+        email: user.email.clone(),
+        expires_at: Utc::now() + Duration::hours(app_config.email_verification_ttl_hours()),
+    };
+    email_verification_repository::insert(&state.pool, new_token_db).await?;
+
+    let message = EmailMessage {
+        to: user.email.clone(),
+        subject: String::from("Confirm your email address"),
+        body: format!(
+            "Hello {},\n\nPlease confirm your email address by opening the link below. It expires in {} hours.\n\n{}/v1/users/verify-email?token={}",
+            user.username,
+            app_config.email_verification_ttl_hours(),
+            app_config.public_url(),
+            token
+        ),
+    };
+
+    if let Err(err) = state.mailer.send(&message).await {
+        tracing::error!("Failed to send verification email: {}", err);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use chrono::{Duration, Utc};
+
+use crate::config::config;
+use crate::domain::models::login_throttle::{ACCOUNT_SCOPE, IP_SCOPE};
+use crate::domain::models::user::UserError;
+use crate::infra::repositories::login_throttle_repository;
+use crate::AppState;
+
+// Rejects the attempt while the account is locked or backing off, or while the
+// client address is throttled. Accounts are keyed by the lowercased submitted
+// username so unknown usernames are throttled exactly like existing ones.
+pub async fn ensure_login_allowed(state: &AppState, username: &str, ip_address: &str) -> Result<(), UserError> {
+    let app_config = config().await;
+    let now = Utc::now();
+
+    let account = login_throttle_repository::get(&state.pool, ACCOUNT_SCOPE, username.to_lowercase())
+        .await
+        .map_err(UserError::InfraError)?;
+
+    if let Some(account) = account {
+        if let Some(locked_until) = account.locked_until {
+            if locked_until > now {
+                return Err(UserError::AccountLocked(locked_until));
+            }
+        } else if account.last_failed_at + Duration::minutes(app_config.login_failure_window_minutes()) > now {
+            let retry_at = account.last_failed_at + backoff_delay(account.failed_attempts).await;
+            if retry_at > now {
+                return Err(UserError::TooManyAttempts(retry_after_seconds(retry_at - now)));
+            }
+        }
+    }
+
+    let ip = login_throttle_repository::get(&state.pool, IP_SCOPE, ip_address.to_string())
+        .await
+        .map_err(UserError::InfraError)?;
+
+    if let Some(locked_until) = ip.and_then(|ip| ip.locked_until) {
+        if locked_until > now {
+            return Err(UserError::TooManyAttempts(retry_after_seconds(locked_until - now)));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn record_login_failure(state: &AppState, username: &str, ip_address: &str) -> Result<(), UserError> {
+    let app_config = config().await;
+    let window = Duration::minutes(app_config.login_failure_window_minutes());
+    let lockout = Duration::minutes(app_config.login_lockout_minutes());
+
+    let account = login_throttle_repository::record_failure(
+        &state.pool,
+        ACCOUNT_SCOPE,
+        username.to_lowercase(),
+        window,
+        app_config.login_max_failed_attempts(),
+        lockout,
+    )
+    .await
+    .map_err(UserError::InfraError)?;
+
+    if account.failed_attempts == app_config.login_max_failed_attempts() {
+        tracing::warn!(target: "security", username = %username, ip_address = %ip_address, "Account locked after repeated failed logins");
+    }
+
+    let ip = login_throttle_repository::record_failure(
+        &state.pool,
+        IP_SCOPE,
+        ip_address.to_string(),
+        window,
+        app_config.login_ip_max_failed_attempts(),
+        lockout,
+    )
+    .await
+    .map_err(UserError::InfraError)?;
+
+    if ip.failed_attempts == app_config.login_ip_max_failed_attempts() {
+        tracing::warn!(target: "security", ip_address = %ip_address, "Address throttled after repeated failed logins");
+    }
+
+    Ok(())
+}
+
+// A successful login forgets the account's failures, the address keeps its count until the window expires
+pub async fn clear_account_failures(state: &AppState, username: &str) -> Result<(), UserError> {
+    login_throttle_repository::clear(&state.pool, ACCOUNT_SCOPE, username.to_lowercase())
+        .await
+        .map_err(UserError::InfraError)?;
+
+    Ok(())
+}
+
+// Doubles the wait after every consecutive failure, up to the configured maximum
+async fn backoff_delay(failed_attempts: i32) -> Duration {
+    let app_config = config().await;
+    let exponent = (failed_attempts - 1).clamp(0, 30) as u32;
+    let seconds = app_config
+        .login_backoff_base_seconds()
+        .saturating_mul(1 << exponent)
+        .min(app_config.login_backoff_max_seconds());
+
+    Duration::seconds(seconds)
+}
+
+fn retry_after_seconds(remaining: Duration) -> i64 {
+    // Rounded up so clients never retry a moment too early
+    (remaining.num_milliseconds() + 999) / 1000
+}
@@ -0,0 +1,60 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domain::models::user::{UserError, UserModel};
+use crate::domain::services::login_throttle::{clear_account_failures, ensure_login_allowed, record_login_failure};
+use crate::handlers::tokens::hash_token;
+use crate::infra::repositories::mfa_repository;
+use crate::utils::totp::{decrypt_secret, is_totp_code, normalize_recovery_code, verify_code};
+use crate::AppState;
+
+// Checks a TOTP code or a recovery code against an enabled second factor.
+// Accepted codes are burned: TOTP steps cannot be reused and recovery codes are single-use.
+pub async fn verify_second_factor(state: &AppState, user_id: Uuid, code: &str) -> Result<bool, UserError> {
+    let totp = mfa_repository::get_totp(&state.pool, user_id)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    let (Some(encrypted_secret), Some(_)) = (totp.totp_secret, totp.totp_enabled_at) else {
+        return Ok(false);
+    };
+
+    if !is_totp_code(code) {
+        return mfa_repository::consume_recovery_code(
+            &state.pool,
+            user_id,
+            hash_token(&normalize_recovery_code(code)),
+        )
+        .await
+        .map_err(UserError::InfraError);
+    }
+
+    let secret = decrypt_secret(&encrypted_secret)
+        .await
+        .ok_or(UserError::InternalServerError)?;
+
+    match verify_code(&secret, code, Utc::now().timestamp()) {
+        Some(step) => mfa_repository::use_totp_step(&state.pool, user_id, step)
+            .await
+            .map_err(UserError::InfraError),
+        None => Ok(false),
+    }
+}
+
+// Checks a second factor code under the login throttle: wrong codes count as failed
+// logins of the account and the address, and only an accepted code clears the account's failures
+pub async fn verify_throttled_second_factor(
+    state: &AppState,
+    user: &UserModel,
+    ip_address: &str,
+    code: &str,
+) -> Result<(), UserError> {
+    ensure_login_allowed(state, &user.username, ip_address).await?;
+
+    if !verify_second_factor(state, user.id, code).await? {
+        record_login_failure(state, &user.username, ip_address).await?;
+        return Err(UserError::InvalidMfaCode);
+    }
+
+    clear_account_failures(state, &user.username).await
+}
@@ -0,0 +1,6 @@
+pub mod email_verification;
+pub mod login_throttle;
+pub mod mfa;
+pub mod password_hashing;
+pub mod password_policy;
+pub mod sessions;
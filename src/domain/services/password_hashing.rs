@@ -0,0 +1,136 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{Error as PasswordHashError, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2, KeyId, Params, ParamsBuilder, Version};
+
+use crate::config::{config, Config};
+use crate::domain::models::user::UserError;
+use crate::infra::blocking_pool::BlockingPoolError;
+use crate::infra::metrics::{PASSWORD_HASH_DURATION, PASSWORD_HASH_REJECTED};
+use crate::AppState;
+
+// Recorded as the `keyid` of hashes computed with the pepper, so hashes from before
+// the pepper was configured can still be verified and then upgraded
+const PEPPER_KEY_ID: &[u8] = b"pepper";
+
+#[derive(Debug, PartialEq)]
+pub enum PasswordVerification {
+    Invalid,
+    // `needs_rehash` is set when the hash was produced with other parameters than the current ones
+    Valid { needs_rehash: bool },
+}
+
+// Hashes a password with the configured algorithm, cost parameters and pepper
+pub async fn hash_password(state: &AppState, password: &str) -> Result<String, UserError> {
+    let hasher = hasher(config().await)?;
+    let password = password.to_owned();
+
+    run_on_hashing_pool(state, "hash", move || {
+        let salt = SaltString::generate(&mut OsRng);
+        hasher
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await?
+    .map_err(UserError::PasswordHashError)
+}
+
+pub async fn verify_password(
+    state: &AppState,
+    password: &str,
+    password_hash: &str,
+) -> Result<PasswordVerification, UserError> {
+    let app_config = config().await;
+    let password = password.to_owned();
+    let password_hash = password_hash.to_owned();
+
+    run_on_hashing_pool(state, "verify", move || {
+        verify_password_blocking(app_config, &password, &password_hash)
+    })
+    .await
+}
+
+// Runs on the hashing pool and records how long the computation took. A full queue is
+// reported as a temporary unavailability rather than making callers wait indefinitely.
+async fn run_on_hashing_pool<F, R>(state: &AppState, operation: &'static str, job: F) -> Result<R, UserError>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    state
+        .hashing_pool
+        .run(move || {
+            let _timer = PASSWORD_HASH_DURATION.with_label_values(&[operation]).start_timer();
+            job()
+        })
+        .await
+        .map_err(|err| match err {
+            BlockingPoolError::Saturated => {
+                PASSWORD_HASH_REJECTED.inc();
+                UserError::HashingUnavailable
+            }
+            BlockingPoolError::Failed => {
+                tracing::error!("Password {} job failed: {}", operation, err);
+                UserError::InternalServerError
+            }
+        })
+}
+
+fn verify_password_blocking(app_config: &'static Config, password: &str, password_hash: &str) -> PasswordVerification {
+    let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
+        return PasswordVerification::Invalid;
+    };
+
+    // The cost parameters are read from the hash itself, only the pepper has to be picked
+    let peppered = has_pepper_key_id(&parsed_hash);
+    let verifier = match (peppered, app_config.password_pepper()) {
+        (true, Some(pepper)) => Argon2::new_with_secret(pepper, Default::default(), Version::default(), Params::DEFAULT),
+        (true, None) => {
+            tracing::error!("Password hash requires a pepper but PASSWORD_PEPPER is not set");
+            return PasswordVerification::Invalid;
+        }
+        (false, _) => Ok(Argon2::default()),
+    };
+
+    match verifier.map(|verifier| verifier.verify_password(password.as_bytes(), &parsed_hash)) {
+        Ok(Ok(())) => PasswordVerification::Valid {
+            needs_rehash: needs_rehash(app_config, &parsed_hash),
+        },
+        _ => PasswordVerification::Invalid,
+    }
+}
+
+fn hasher(app_config: &'static Config) -> Result<Argon2<'static>, PasswordHashError> {
+    let algorithm = app_config.password_hash_algorithm();
+    let params = app_config.password_hash_params();
+
+    match app_config.password_pepper() {
+        Some(pepper) => {
+            let params = ParamsBuilder::new()
+                .m_cost(params.m_cost())
+                .t_cost(params.t_cost())
+                .p_cost(params.p_cost())
+                .keyid(KeyId::new(PEPPER_KEY_ID)?)
+                .build()?;
+            Ok(Argon2::new_with_secret(pepper, algorithm, Version::default(), params)?)
+        }
+        None => Ok(Argon2::new(algorithm, Version::default(), params.clone())),
+    }
+}
+
+fn has_pepper_key_id(parsed_hash: &PasswordHash) -> bool {
+    Params::try_from(parsed_hash).is_ok_and(|params| params.keyid() == PEPPER_KEY_ID)
+}
+
+fn needs_rehash(app_config: &Config, parsed_hash: &PasswordHash) -> bool {
+    let Ok(params) = Params::try_from(parsed_hash) else {
+        return true;
+    };
+    let expected = app_config.password_hash_params();
+
+    parsed_hash.algorithm.as_str() != app_config.password_hash_algorithm().as_str()
+        || parsed_hash.version != Some(Version::default().into())
+        || params.m_cost() != expected.m_cost()
+        || params.t_cost() != expected.t_cost()
+        || params.p_cost() != expected.p_cost()
+        || has_pepper_key_id(parsed_hash) != app_config.password_pepper().is_some()
+}
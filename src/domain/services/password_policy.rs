@@ -0,0 +1,92 @@
+use crate::config::config;
+use crate::domain::models::user::{PasswordPolicyViolation, UserError};
+use crate::utils::password_strength::{estimate_score, MAX_SCORE};
+use crate::AppState;
+
+// Parts of the username or email shorter than this are not looked for in passwords
+const MIN_PERSONAL_INFO_LENGTH: usize = 3;
+
+// Checks a new password against the configured policy, reporting every broken rule at once.
+// `username` and `email` are the values the account will have once the change is applied.
+pub async fn enforce_password_policy(
+    state: &AppState,
+    password: &str,
+    username: &str,
+    email: &str,
+) -> Result<(), UserError> {
+    let app_config = config().await;
+    let mut violations = Vec::new();
+
+    let min_length = app_config.password_min_length();
+    if password.chars().count() < min_length {
+        violations.push(PasswordPolicyViolation {
+            code: "too_short",
+            message: format!("Password must be at least {} characters long", min_length),
+        });
+    }
+
+    let min_character_classes = app_config.password_min_character_classes();
+    if character_classes(password) < min_character_classes {
+        violations.push(PasswordPolicyViolation {
+            code: "character_classes",
+            message: format!(
+                "Password must mix at least {} of lowercase letters, uppercase letters, digits and symbols",
+                min_character_classes
+            ),
+        });
+    }
+
+    let local_part = email.split('@').next().unwrap_or_default();
+    if contains_personal_info(password, &[username, email, local_part]) {
+        violations.push(PasswordPolicyViolation {
+            code: "contains_personal_info",
+            message: String::from("Password must not contain the username or email address"),
+        });
+    }
+
+    let min_score = app_config.password_min_strength_score();
+    let score = estimate_score(password, &[username, local_part]);
+    if score < min_score {
+        violations.push(PasswordPolicyViolation {
+            code: "too_weak",
+            message: format!(
+                "Password is too easy to guess (strength {} of {}, at least {} required)",
+                score, MAX_SCORE, min_score
+            ),
+        });
+    }
+
+    if state.breached_passwords.contains(password) {
+        violations.push(PasswordPolicyViolation {
+            code: "breached",
+            message: String::from("Password has appeared in a data breach, please choose another one"),
+        });
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(UserError::WeakPassword(violations))
+    }
+}
+
+fn character_classes(password: &str) -> usize {
+    [
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ]
+    .into_iter()
+    .filter(|present| *present)
+    .count()
+}
+
+fn contains_personal_info(password: &str, personal_info: &[&str]) -> bool {
+    let password = password.to_lowercase();
+    personal_info
+        .iter()
+        .map(|value| value.to_lowercase())
+        .filter(|value| value.chars().count() >= MIN_PERSONAL_INFO_LENGTH)
+        .any(|value| password.contains(&value))
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::config;
+use crate::domain::models::user::{UserError, UserModel};
+use crate::handlers::tokens::{generate_token, hash_token};
+use crate::infra::repositories::{role_repository, token_repository};
+use crate::utils::jwt::issue_access_token;
+use crate::AppState;
+
+pub struct IssuedSession {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub access_token: String,
+    pub access_token_expires_at: DateTime<Utc>,
+}
+
+// Creates the refresh token backing a new session and signs its first access token
+pub async fn issue_session(
+    state: &AppState,
+    user: &UserModel,
+    ip_address: String,
+    user_agent: String,
+) -> Result<IssuedSession, UserError> {
+    // Only the hash of the token is stored, the raw value is returned once to the client
+    let token = generate_token();
+    let now = Utc::now();
+    let new_token_db = token_repository::NewTokenDb {
+        user_id: user.id,
+        token_hash: hash_token(&token),
+        created_at: now,
+        expires_at: now + Duration::minutes(config().await.token_ttl_minutes()),
+        ip_address,
+        user_agent,
+        previous_token_id: None,
+    };
+
+    let created_token = token_repository::insert(&state.pool, new_token_db)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    let roles = role_repository::get_user_roles(&state.pool, user.id)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    let (access_token, access_token_expires_at) = issue_access_token(user, &roles, created_token.id)
+        .await
+        .map_err(UserError::TokenSigningError)?;
+
+    Ok(IssuedSession {
+        token,
+        expires_at: created_token.expires_at,
+        access_token,
+        access_token_expires_at,
+    })
+}
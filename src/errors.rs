@@ -1,30 +1,223 @@
-use axum::http::StatusCode;
+use std::fmt;
+
+use axum::http::header::{CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderValue, StatusCode};
 use axum::Json;
 use axum::response::IntoResponse;
-use serde_json::json;
+use serde_json::{json, Map, Value};
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::infra::errors::InfraError;
+use crate::utils::correlation_id::current_correlation_id;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
 
 #[derive(Debug)]
 pub enum AppError {
-    InternalServerError,
+    // Carries the description of the cause, which is logged but never returned
+    InternalServerError(String),
     BodyParsingError(String),
+    InvalidPayload(ValidationErrors),
 }
 
-pub fn internal_error<E>(_err: E) -> AppError {
-    AppError::InternalServerError
+pub fn internal_error<E: fmt::Display>(err: E) -> AppError {
+    AppError::InternalServerError(err.to_string())
 }
 
-impl IntoResponse for AppError {
+// RFC 7807 body shared by every error type of the API. `code` is stable and meant
+// for clients to match on, `detail` is human readable and may change.
+#[derive(Debug)]
+pub struct Problem {
+    status: StatusCode,
+    code: &'static str,
+    detail: String,
+    retry_after: Option<i64>,
+    // Extension members added to the body next to the standard ones
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, code: &'static str, detail: impl Into<String>) -> Self {
+        Problem {
+            status,
+            code,
+            detail: detail.into(),
+            retry_after: None,
+            extensions: Map::new(),
+        }
+    }
+
+    // The cause is only logged, clients get a generic message and the correlation id
+    pub fn internal(cause: impl fmt::Display) -> Self {
+        tracing::error!(cause = %cause, "Internal server error");
+        Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            "An internal error occurred",
+        )
+    }
+
+    pub fn with_retry_after(mut self, seconds: i64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    pub fn with_extension(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.extensions.insert(name.to_string(), value.into());
+        self
+    }
+}
+
+impl IntoResponse for Problem {
     fn into_response(self) -> axum::response::Response {
-        let (status, err_msg) = match self {
-            Self::InternalServerError => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                String::from("Internal Server Error"),
+        let mut body = json!({
+            "type": "about:blank",
+            "title": self.status.canonical_reason().unwrap_or("Error"),
+            "status": self.status.as_u16(),
+            "detail": self.detail,
+            "code": self.code,
+            "correlation_id": current_correlation_id(),
+        });
+        if let Value::Object(members) = &mut body {
+            for (name, value) in self.extensions {
+                members.entry(name).or_insert(value);
+            }
+        }
+
+        let mut response = (self.status, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        if let Some(seconds) = self.retry_after {
+            response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+// Storage failures the client can act on get their own status, the rest is a plain 500
+impl From<InfraError> for Problem {
+    fn from(error: InfraError) -> Self {
+        match error {
+            InfraError::NotFound => Problem::new(
+                StatusCode::NOT_FOUND,
+                "not_found",
+                "The requested resource was not found",
             ),
-            Self::BodyParsingError(message) => (
+            InfraError::UniqueViolation { .. } => {
+                tracing::info!(cause = %error, "Request conflicts with existing data");
+                Problem::new(
+                    StatusCode::CONFLICT,
+                    "conflict",
+                    "The request conflicts with an existing resource",
+                )
+            }
+            InfraError::ForeignKeyViolation { .. } => {
+                tracing::info!(cause = %error, "Request references missing data");
+                Problem::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "invalid_reference",
+                    "The request references a resource that does not exist",
+                )
+            }
+            InfraError::SerializationFailure(_) => {
+                tracing::warn!(cause = %error, "Transaction aborted by a concurrent update");
+                Problem::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "concurrent_update",
+                    "The request conflicted with a concurrent update, please retry",
+                )
+                .with_retry_after(1)
+            }
+            InfraError::PoolTimeout(_) | InfraError::ConnectionLost(_) => {
+                tracing::error!(cause = %error, "Database unavailable");
+                Problem::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "service_unavailable",
+                    "The service is temporarily unavailable, please retry",
+                )
+                .with_retry_after(5)
+            }
+            InfraError::InternalServerError(_) => Problem::internal(error),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let problem = match self {
+            Self::InternalServerError(cause) => Problem::internal(cause),
+            Self::BodyParsingError(message) => Problem::new(
                 StatusCode::BAD_REQUEST,
+                "invalid_request",
                 format!("Bad request error: {}", message),
             ),
+            Self::InvalidPayload(errors) => Problem::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                "The request payload is invalid",
+            )
+            .with_extension("errors", adapt_validation_errors(&errors)),
+        };
+        problem.into_response()
+    }
+}
+
+// `{"field": [{"code": "length", "message": "..."}]}`, nested payloads use dotted paths
+fn adapt_validation_errors(errors: &ValidationErrors) -> Value {
+    let mut fields = Map::new();
+    collect_validation_errors(errors, "", &mut fields);
+    Value::Object(fields)
+}
+
+fn collect_validation_errors(errors: &ValidationErrors, prefix: &str, fields: &mut Map<String, Value>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
         };
-        (status, Json(json!({ "message": err_msg }))).into_response()
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                let field_errors = field_errors
+                    .iter()
+                    .map(|error| json!({ "code": error.code, "message": validation_message(error) }))
+                    .collect();
+                fields.insert(path, Value::Array(field_errors));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_validation_errors(nested, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_validation_errors(nested, &format!("{}[{}]", path, index), fields);
+                }
+            }
+        }
+    }
+}
+
+fn validation_message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(Value::to_string);
+    match error.code.as_ref() {
+        "length" => match (param("min"), param("max"), param("equal")) {
+            (_, _, Some(equal)) => format!("Must be exactly {} characters long", equal),
+            (Some(min), Some(max), _) => format!("Must be between {} and {} characters long", min, max),
+            (Some(min), None, _) => format!("Must be at least {} characters long", min),
+            (None, Some(max), _) => format!("Must be at most {} characters long", max),
+            _ => String::from("Has an invalid length"),
+        },
+        "range" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("Must be between {} and {}", min, max),
+            (Some(min), None) => format!("Must be at least {}", min),
+            (None, Some(max)) => format!("Must be at most {}", max),
+            _ => String::from("Is out of range"),
+        },
+        "email" => String::from("Must be a valid email address"),
+        "regex" => String::from("Has an invalid format"),
+        _ => String::from("Is invalid"),
     }
 }
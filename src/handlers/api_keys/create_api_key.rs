@@ -0,0 +1,86 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::api_key::{ApiKeyError, ApiKeyModel};
+use crate::handlers::api_keys::{generate_api_key, ApiKeyResponse, CreateApiKeyRequest, CreateApiKeyResponse};
+use crate::handlers::tokens::hash_token;
+use crate::infra::repositories::api_key_repository::NewApiKeyDb;
+use crate::infra::repositories::{api_key_repository, role_repository};
+use crate::utils::{AuthenticatedUser, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+    JsonExtractor(new_api_key): JsonExtractor<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), ApiKeyError> {
+    // Keys are personal: they are only minted by their owner, from an interactive session
+    if !auth.is_self(user_id) {
+        return Err(ApiKeyError::Forbidden);
+    }
+
+    let permissions = role_repository::get_all_permissions(&state.pool)
+        .await
+        .map_err(ApiKeyError::InfraError)?;
+
+    if let Some(unknown) = new_api_key
+        .scopes
+        .iter()
+        .find(|scope| !permissions.iter().any(|permission| &permission.name == *scope))
+    {
+        return Err(ApiKeyError::UnknownScope(unknown.clone()));
+    }
+
+    // Scopes are checked again on every use, in case the owner loses a permission later
+    let user_permissions = role_repository::get_user_permissions(&state.pool, user_id)
+        .await
+        .map_err(ApiKeyError::InfraError)?;
+
+    if let Some(not_granted) = new_api_key
+        .scopes
+        .iter()
+        .find(|scope| !user_permissions.contains(scope))
+    {
+        return Err(ApiKeyError::ScopeNotGranted(not_granted.clone()));
+    }
+
+    let (key, prefix) = generate_api_key();
+
+    let new_api_key_db = NewApiKeyDb {
+        user_id,
+        name: new_api_key.name,
+        prefix,
+        key_hash: hash_token(&key),
+        scopes: new_api_key.scopes,
+        expires_at: new_api_key.expires_at,
+    };
+
+    let created_api_key = api_key_repository::insert(&state.pool, new_api_key_db)
+        .await
+        .map_err(ApiKeyError::InfraError)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateApiKeyResponse {
+            key,
+            details: adapt_api_key_to_api_key_response(created_api_key),
+        }),
+    ))
+}
+
+fn adapt_api_key_to_api_key_response(api_key: ApiKeyModel) -> ApiKeyResponse {
+    ApiKeyResponse {
+        id: api_key.id,
+        name: api_key.name,
+        prefix: api_key.prefix,
+        scopes: api_key.scopes,
+        created_at: api_key.created_at,
+        expires_at: api_key.expires_at,
+        last_used_at: api_key.last_used_at,
+        last_used_ip: api_key.last_used_ip,
+        revoked_at: api_key.revoked_at,
+    }
+}
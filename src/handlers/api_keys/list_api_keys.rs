@@ -0,0 +1,44 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::api_key::{ApiKeyError, ApiKeyModel};
+use crate::handlers::api_keys::{ApiKeyResponse, ListApiKeysResponse};
+use crate::infra::repositories::api_key_repository;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+) -> Result<Json<ListApiKeysResponse>, ApiKeyError> {
+    if !auth.is_self(user_id) && !auth.can("users:read") {
+        return Err(ApiKeyError::Forbidden);
+    }
+
+    let api_keys = api_key_repository::get_all_for_user(&state.pool, user_id)
+        .await
+        .map_err(ApiKeyError::InfraError)?;
+
+    Ok(Json(ListApiKeysResponse {
+        api_keys: api_keys
+            .into_iter()
+            .map(adapt_api_key_to_api_key_response)
+            .collect(),
+    }))
+}
+
+fn adapt_api_key_to_api_key_response(api_key: ApiKeyModel) -> ApiKeyResponse {
+    ApiKeyResponse {
+        id: api_key.id,
+        name: api_key.name,
+        prefix: api_key.prefix,
+        scopes: api_key.scopes,
+        created_at: api_key.created_at,
+        expires_at: api_key.expires_at,
+        last_used_at: api_key.last_used_at,
+        last_used_ip: api_key.last_used_ip,
+        revoked_at: api_key.revoked_at,
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+pub use create_api_key::create_api_key;
+pub use list_api_keys::list_api_keys;
+pub use revoke_api_key::revoke_api_key;
+
+mod create_api_key;
+mod list_api_keys;
+mod revoke_api_key;
+
+// Lets the authentication extractor tell API keys apart from session tokens
+pub const API_KEY_PREFIX: &str = "nmk_";
+
+// Number of characters of the key, prefix included, kept in clear to identify it in listings
+const API_KEY_DISPLAY_LENGTH: usize = 12;
+
+// Generates a new API key, returning the raw key and its displayable prefix
+pub fn generate_api_key() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let key = format!("{}{}", API_KEY_PREFIX, hex::encode(bytes));
+    let prefix = key[..API_KEY_DISPLAY_LENGTH].to_string();
+    (key, prefix)
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 64))]
+    name: String,
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyResponse {
+    id: Uuid,
+    name: String,
+    prefix: String,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    last_used_ip: Option<String>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateApiKeyResponse {
+    // Only ever shown once, it cannot be recovered afterwards
+    key: String,
+    #[serde(flatten)]
+    details: ApiKeyResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListApiKeysResponse {
+    api_keys: Vec<ApiKeyResponse>,
+}
@@ -0,0 +1,39 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::domain::models::api_key::ApiKeyError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::api_key_repository;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor((user_id, api_key_id)): PathExtractor<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiKeyError> {
+    if !auth.is_self(user_id) && !auth.can("users:write") {
+        return Err(ApiKeyError::Forbidden);
+    }
+
+    let api_key = api_key_repository::get(&state.pool, api_key_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => ApiKeyError::NotFound(api_key_id),
+            db_error => ApiKeyError::InfraError(db_error),
+        })?;
+
+    // Keys of other users are reported as missing rather than forbidden
+    if api_key.user_id != user_id {
+        return Err(ApiKeyError::NotFound(api_key_id));
+    }
+
+    if api_key.revoked_at.is_none() {
+        api_key_repository::revoke(&state.pool, api_key.id)
+            .await
+            .map_err(ApiKeyError::InfraError)?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,2 +1,4 @@
 pub mod users;
-pub mod tokens;
\ No newline at end of file
+pub mod tokens;
+pub mod roles;
+pub mod api_keys;
\ No newline at end of file
@@ -0,0 +1,47 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::domain::models::role::RoleError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::{role_repository, user_repository};
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn assign_user_role(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor((user_id, role_id)): PathExtractor<(Uuid, Uuid)>,
+) -> Result<StatusCode, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    user_repository::get(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => RoleError::UserNotFound(user_id),
+            db_error => RoleError::InfraError(db_error),
+        })?;
+
+    let role = role_repository::get(&state.pool, role_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => RoleError::NotFound(role_id),
+            db_error => RoleError::InfraError(db_error),
+        })?;
+
+    role_repository::assign_to_user(&state.pool, user_id, role.id)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    tracing::info!(
+        target: "security",
+        granted_by = %auth.user_id,
+        user_id = %user_id,
+        role = %role.name,
+        "Role granted"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,43 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+
+use crate::domain::models::role::{RoleError, RoleModel};
+use crate::handlers::roles::{resolve_permission_ids, CreateRoleRequest, RoleResponse};
+use crate::infra::repositories::role_repository;
+use crate::infra::repositories::role_repository::NewRoleDb;
+use crate::utils::{AuthenticatedUser, JsonExtractor};
+use crate::AppState;
+
+pub async fn create_role(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    JsonExtractor(new_role): JsonExtractor<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<RoleResponse>), RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let permission_ids = resolve_permission_ids(&state.pool, &new_role.permissions).await?;
+
+    let new_role_db = NewRoleDb {
+        name: new_role.name,
+        description: new_role.description.unwrap_or_default(),
+    };
+
+    let created_role = role_repository::insert(&state.pool, new_role_db, permission_ids)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    Ok((StatusCode::CREATED, Json(adapt_role_to_role_response(created_role))))
+}
+
+fn adapt_role_to_role_response(role: RoleModel) -> RoleResponse {
+    RoleResponse {
+        id: role.id,
+        name: role.name,
+        description: role.description,
+        permissions: role.permissions,
+        created_at: role.created_at,
+    }
+}
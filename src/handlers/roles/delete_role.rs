@@ -0,0 +1,40 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::domain::models::role::RoleError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::role_repository::{self, ADMIN_ROLE};
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn delete_role(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(role_id): PathExtractor<Uuid>,
+) -> Result<StatusCode, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let role = role_repository::get(&state.pool, role_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => RoleError::NotFound(role_id),
+            db_error => RoleError::InfraError(db_error),
+        })?;
+
+    if role.name == ADMIN_ROLE {
+        return Err(RoleError::BuiltInRole(role.name));
+    }
+
+    let deleted = role_repository::delete(&state.pool, role_id)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    if deleted == 0 {
+        return Err(RoleError::NotFound(role_id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,39 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::role::{RoleError, RoleModel};
+use crate::handlers::roles::RoleResponse;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::role_repository;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn get_role(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(role_id): PathExtractor<Uuid>,
+) -> Result<Json<RoleResponse>, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let role = role_repository::get(&state.pool, role_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => RoleError::NotFound(role_id),
+            db_error => RoleError::InfraError(db_error),
+        })?;
+
+    Ok(Json(adapt_role_to_role_response(role)))
+}
+
+fn adapt_role_to_role_response(role: RoleModel) -> RoleResponse {
+    RoleResponse {
+        id: role.id,
+        name: role.name,
+        description: role.description,
+        permissions: role.permissions,
+        created_at: role.created_at,
+    }
+}
@@ -0,0 +1,36 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::role::{PermissionModel, RoleError};
+use crate::handlers::roles::{ListPermissionsResponse, PermissionResponse};
+use crate::infra::repositories::role_repository;
+use crate::utils::AuthenticatedUser;
+use crate::AppState;
+
+pub async fn list_permissions(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+) -> Result<Json<ListPermissionsResponse>, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let permissions = role_repository::get_all_permissions(&state.pool)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    Ok(Json(ListPermissionsResponse {
+        permissions: permissions
+            .into_iter()
+            .map(adapt_permission_to_permission_response)
+            .collect(),
+    }))
+}
+
+fn adapt_permission_to_permission_response(permission: PermissionModel) -> PermissionResponse {
+    PermissionResponse {
+        id: permission.id,
+        name: permission.name,
+        description: permission.description,
+    }
+}
@@ -0,0 +1,35 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::role::{RoleError, RoleModel};
+use crate::handlers::roles::{ListRolesResponse, RoleResponse};
+use crate::infra::repositories::role_repository;
+use crate::utils::AuthenticatedUser;
+use crate::AppState;
+
+pub async fn list_roles(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+) -> Result<Json<ListRolesResponse>, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let roles = role_repository::get_all(&state.pool)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    Ok(Json(ListRolesResponse {
+        roles: roles.into_iter().map(adapt_role_to_role_response).collect(),
+    }))
+}
+
+fn adapt_role_to_role_response(role: RoleModel) -> RoleResponse {
+    RoleResponse {
+        id: role.id,
+        name: role.name,
+        description: role.description,
+        permissions: role.permissions,
+        created_at: role.created_at,
+    }
+}
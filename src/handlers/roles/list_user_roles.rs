@@ -0,0 +1,38 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::role::{RoleError, RoleModel};
+use crate::handlers::roles::{ListRolesResponse, RoleResponse};
+use crate::infra::repositories::role_repository;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn list_user_roles(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+) -> Result<Json<ListRolesResponse>, RoleError> {
+    // Users can always see their own roles
+    if auth.user_id != user_id && !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let roles = role_repository::get_user_roles(&state.pool, user_id)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    Ok(Json(ListRolesResponse {
+        roles: roles.into_iter().map(adapt_role_to_role_response).collect(),
+    }))
+}
+
+fn adapt_role_to_role_response(role: RoleModel) -> RoleResponse {
+    RoleResponse {
+        id: role.id,
+        name: role.name,
+        description: role.description,
+        permissions: role.permissions,
+        created_at: role.created_at,
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::models::role::RoleError;
+use crate::infra::repositories::role_repository;
+
+pub use assign_user_role::assign_user_role;
+pub use create_role::create_role;
+pub use delete_role::delete_role;
+pub use get_role::get_role;
+pub use list_permissions::list_permissions;
+pub use list_roles::list_roles;
+pub use list_user_roles::list_user_roles;
+pub use remove_user_role::remove_user_role;
+pub use set_role_permissions::set_role_permissions;
+
+mod assign_user_role;
+mod create_role;
+mod delete_role;
+mod get_role;
+mod list_permissions;
+mod list_roles;
+mod list_user_roles;
+mod remove_user_role;
+mod set_role_permissions;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateRoleRequest {
+    #[validate(length(min = 1, max = 64))]
+    name: String,
+    #[validate(length(max = 512))]
+    description: Option<String>,
+    #[validate(length(max = 100))]
+    permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetRolePermissionsRequest {
+    #[validate(length(max = 100))]
+    permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleResponse {
+    id: Uuid,
+    name: String,
+    description: String,
+    permissions: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListRolesResponse {
+    roles: Vec<RoleResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermissionResponse {
+    id: Uuid,
+    name: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListPermissionsResponse {
+    permissions: Vec<PermissionResponse>,
+}
+
+// Maps permission names to their ids, rejecting any name that does not exist
+async fn resolve_permission_ids(
+    pool: &deadpool_diesel::postgres::Pool,
+    names: &[String],
+) -> Result<Vec<Uuid>, RoleError> {
+    let permissions = role_repository::get_all_permissions(pool)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    names
+        .iter()
+        .map(|name| {
+            permissions
+                .iter()
+                .find(|permission| &permission.name == name)
+                .map(|permission| permission.id)
+                .ok_or_else(|| RoleError::UnknownPermission(name.clone()))
+        })
+        .collect()
+}
@@ -0,0 +1,51 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::domain::models::role::RoleError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::role_repository::{self, ADMIN_ROLE};
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn remove_user_role(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor((user_id, role_id)): PathExtractor<(Uuid, Uuid)>,
+) -> Result<StatusCode, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    // Prevents administrators from locking themselves out, like `set_user_admin`
+    if auth.user_id == user_id {
+        let role = role_repository::get(&state.pool, role_id)
+            .await
+            .map_err(|db_error| match db_error {
+                InfraError::NotFound => RoleError::NotFound(role_id),
+                db_error => RoleError::InfraError(db_error),
+            })?;
+
+        if role.name == ADMIN_ROLE {
+            return Err(RoleError::Forbidden);
+        }
+    }
+
+    let removed = role_repository::remove_from_user(&state.pool, user_id, role_id)
+        .await
+        .map_err(RoleError::InfraError)?;
+
+    if removed == 0 {
+        return Err(RoleError::NotFound(role_id));
+    }
+
+    tracing::info!(
+        target: "security",
+        revoked_by = %auth.user_id,
+        user_id = %user_id,
+        role_id = %role_id,
+        "Role revoked"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,42 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::role::{RoleError, RoleModel};
+use crate::handlers::roles::{resolve_permission_ids, RoleResponse, SetRolePermissionsRequest};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::role_repository;
+use crate::utils::{AuthenticatedUser, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+pub async fn set_role_permissions(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(role_id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<SetRolePermissionsRequest>,
+) -> Result<Json<RoleResponse>, RoleError> {
+    if !auth.can("roles:manage") {
+        return Err(RoleError::Forbidden);
+    }
+
+    let permission_ids = resolve_permission_ids(&state.pool, &request.permissions).await?;
+
+    let role = role_repository::set_permissions(&state.pool, role_id, permission_ids)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => RoleError::NotFound(role_id),
+            db_error => RoleError::InfraError(db_error),
+        })?;
+
+    Ok(Json(adapt_role_to_role_response(role)))
+}
+
+fn adapt_role_to_role_response(role: RoleModel) -> RoleResponse {
+    RoleResponse {
+        id: role.id,
+        name: role.name,
+        description: role.description,
+        permissions: role.permissions,
+        created_at: role.created_at,
+    }
+}
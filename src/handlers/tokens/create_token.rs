@@ -5,46 +5,79 @@ use axum::Json;
 use headers::UserAgent;
 use axum_extra::TypedHeader;
 use sha2::{Sha256, Digest};
-use crate::domain::models::token::TokenError;
-use crate::handlers::tokens::{CreatTokenRequest, TokenResponse};
+use rand_core::{OsRng, RngCore};
+use crate::config::config;
+use crate::domain::models::token::{TokenError, TokenModel};
+use crate::handlers::tokens::{CreatTokenRequest, CreateTokenResponse, TokenResponse};
 use crate::infra::repositories::token_repository;
 use crate::state::AppState;
-use crate::utils::JsonExtractor;
+use crate::utils::{AuthenticatedUser, JsonExtractor};
 
-fn hash_token(token: &str) -> String {
+pub fn hash_token(token: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     let result = hasher.finalize();
     hex::encode(result)
 }
 
+// Generates a random opaque token; only its hash is ever persisted
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
 
 pub async fn create_token(
     State(state): State<AppState>,
-    JsonExtractor(new_token): JsonExtractor<CreatTokenRequest>,
+    auth: AuthenticatedUser,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    TypedHeader(user_agent): TypedHeader<UserAgent>
-) -> Result<Json<TokenResponse>, TokenError> {
+    user_agent: Option<TypedHeader<UserAgent>>,
+    JsonExtractor(new_token): JsonExtractor<CreatTokenRequest>,
+) -> Result<Json<CreateTokenResponse>, TokenError> {
+    // A session would carry every right of the owner, well beyond the key's scopes
+    if auth.api_key_id.is_some() {
+        return Err(TokenError::Forbidden);
+    }
 
+    let token = generate_token();
+    let max_ttl_minutes = config().await.token_ttl_minutes();
+    let ttl_minutes = new_token
+        .expires_in_minutes
+        .map_or(max_ttl_minutes, |minutes| minutes.min(max_ttl_minutes));
 
     let new_token_db = token_repository::NewTokenDb {
-        token_hash: hash_token(new_token.token.as_str()),
+        user_id: auth.user_id,
+        token_hash: hash_token(&token),
         created_at: Utc::now(),
-        expires_at: Utc::now() + Duration::hours(1),
+        expires_at: Utc::now() + Duration::minutes(ttl_minutes),
         ip_address: addr.ip().to_string(),
-        user_agent: user_agent.to_string(),
+        user_agent: user_agent
+            .map(|TypedHeader(user_agent)| user_agent.to_string())
+            .unwrap_or_default(),
+        previous_token_id: None,
     };
 
     let created_token = token_repository::insert(&state.pool, new_token_db)
         .await
         .map_err(TokenError::InfraError)?;
 
-    let token_response = TokenResponse {
-        id: created_token.id,
-        user_id: created_token.user_id,
-        token_hash: created_token.token_hash,
-        expires_at: created_token.expires_at,
-    };
+    Ok(Json(CreateTokenResponse {
+        token,
+        details: adapt_token_to_token_response(created_token),
+    }))
+}
 
-    Ok(Json(token_response))
+fn adapt_token_to_token_response(token: TokenModel) -> TokenResponse {
+    TokenResponse {
+        id: token.id,
+        user_id: token.user_id,
+        created_at: token.created_at,
+        expires_at: token.expires_at,
+        revoked_at: token.revoked_at,
+        ip_address: token.ip_address,
+        user_agent: token.user_agent,
+        replaced_by: token.replaced_by,
+        previous_token_id: token.previous_token_id,
+    }
 }
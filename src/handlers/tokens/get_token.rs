@@ -0,0 +1,44 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::token::{TokenError, TokenModel};
+use crate::handlers::tokens::TokenResponse;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::token_repository;
+use crate::state::AppState;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+
+pub async fn get_token(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(token_id): PathExtractor<Uuid>,
+) -> Result<Json<TokenResponse>, TokenError> {
+    let token = token_repository::get(&state.pool, token_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => TokenError::NotFound(token_id),
+            db_error => TokenError::InfraError(db_error),
+        })?;
+
+    // Tokens of other users are reported as missing rather than forbidden
+    if !auth.is_self(token.user_id) && !auth.can("sessions:read") {
+        return Err(TokenError::NotFound(token_id));
+    }
+
+    Ok(Json(adapt_token_to_token_response(token)))
+}
+
+fn adapt_token_to_token_response(token: TokenModel) -> TokenResponse {
+    TokenResponse {
+        id: token.id,
+        user_id: token.user_id,
+        created_at: token.created_at,
+        expires_at: token.expires_at,
+        revoked_at: token.revoked_at,
+        ip_address: token.ip_address,
+        user_agent: token.user_agent,
+        replaced_by: token.replaced_by,
+        previous_token_id: token.previous_token_id,
+    }
+}
@@ -0,0 +1,55 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::domain::models::pagination::Page;
+use crate::domain::models::token::{TokenError, TokenModel};
+use crate::handlers::tokens::{ListTokensResponse, TokenResponse};
+use crate::infra::repositories::token_repository::{get_all, TokensFilter};
+use crate::state::AppState;
+use crate::utils::{AuthenticatedUser, QueryExtractor};
+
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    QueryExtractor(mut params): QueryExtractor<TokensFilter>,
+) -> Result<Json<ListTokensResponse>, TokenError> {
+    // Callers without the permission can only ever list their own tokens
+    if !auth.can("sessions:read") {
+        // API keys only see sessions through their scopes
+        if auth.api_key_id.is_some() {
+            return Err(TokenError::Forbidden);
+        }
+        params.user_id = Some(auth.user_id);
+    }
+
+    let tokens = get_all(&state.pool, params)
+        .await
+        .map_err(TokenError::InfraError)?;
+
+    Ok(Json(adapt_tokens_to_list_tokens_response(tokens)))
+}
+
+fn adapt_token_to_token_response(token: TokenModel) -> TokenResponse {
+    TokenResponse {
+        id: token.id,
+        user_id: token.user_id,
+        created_at: token.created_at,
+        expires_at: token.expires_at,
+        revoked_at: token.revoked_at,
+        ip_address: token.ip_address,
+        user_agent: token.user_agent,
+        replaced_by: token.replaced_by,
+        previous_token_id: token.previous_token_id,
+    }
+}
+
+fn adapt_tokens_to_list_tokens_response(page: Page<TokenModel>) -> ListTokensResponse {
+    let tokens_response: Vec<TokenResponse> =
+        page.items.into_iter().map(adapt_token_to_token_response).collect();
+
+    ListTokensResponse {
+        tokens: tokens_response,
+        next_cursor: page.next_cursor,
+        total: page.total,
+    }
+}
@@ -0,0 +1,29 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::Utc;
+
+use crate::domain::models::token::TokenError;
+use crate::infra::repositories::token_repository;
+use crate::infra::repositories::token_repository::UpdateTokenDb;
+use crate::state::AppState;
+use crate::utils::AuthenticatedUser;
+
+pub async fn logout(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+) -> Result<StatusCode, TokenError> {
+    // API keys are not sessions and must be revoked explicitly
+    let session_id = auth.session_id.ok_or(TokenError::InvalidToken)?;
+
+    let changes = UpdateTokenDb {
+        expires_at: None,
+        revoked_at: Some(Utc::now()),
+        replaced_by: None,
+    };
+
+    token_repository::update(&state.pool, session_id, changes)
+        .await
+        .map_err(TokenError::InfraError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
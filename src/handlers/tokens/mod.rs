@@ -1,18 +1,28 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
-mod create_token;
-
+pub use create_token::{create_token, generate_token, hash_token};
+pub use get_token::get_token;
+pub use list_tokens::list_tokens;
+pub use logout::logout;
+pub use refresh_token::refresh_token;
+pub use revoke_token::revoke_token;
 
+mod create_token;
+mod get_token;
+mod list_tokens;
+mod logout;
+mod refresh_token;
+mod revoke_token;
 
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreatTokenRequest {
-    user_id: Uuid,
-    user_agent: String,
-    ip_address: String,
-    pub token: String,
+    // Capped at TOKEN_TTL_MINUTES, a new session never outlives one opened by logging in
+    #[validate(range(min = 1, max = 525600))]
+    expires_in_minutes: Option<i64>,
 }
 
 
@@ -20,6 +30,41 @@ pub struct CreatTokenRequest {
 pub struct TokenResponse {
     id: Uuid,
     user_id: Uuid,
-    token_hash: String,
+    created_at: DateTime<Utc>,
     expires_at: DateTime<Utc>,
-}
\ No newline at end of file
+    revoked_at: Option<DateTime<Utc>>,
+    ip_address: String,
+    user_agent: String,
+    replaced_by: Option<Uuid>,
+    previous_token_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTokenResponse {
+    token: String,
+    #[serde(flatten)]
+    details: TokenResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListTokensResponse {
+    tokens: Vec<TokenResponse>,
+    // Pass it back as `cursor` to get the next page, null on the last page
+    next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1, max = 256))]
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+    access_token: String,
+    access_token_expires_at: DateTime<Utc>,
+}
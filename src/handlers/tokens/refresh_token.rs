@@ -0,0 +1,69 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, State};
+use axum::Json;
+use axum_extra::TypedHeader;
+use chrono::{Duration, Utc};
+use headers::UserAgent;
+
+use crate::config::config;
+use crate::domain::models::token::TokenError;
+use crate::handlers::tokens::{generate_token, hash_token, RefreshTokenRequest, RefreshTokenResponse};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::token_repository::{ReplacementTokenDb, RotationOutcome};
+use crate::infra::repositories::{role_repository, token_repository, user_repository};
+use crate::utils::jwt::issue_access_token;
+use crate::state::AppState;
+use crate::utils::JsonExtractor;
+
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    JsonExtractor(refresh): JsonExtractor<RefreshTokenRequest>,
+) -> Result<Json<RefreshTokenResponse>, TokenError> {
+    let token = generate_token();
+    let now = Utc::now();
+
+    let replacement = ReplacementTokenDb {
+        token_hash: hash_token(&token),
+        created_at: now,
+        expires_at: now + Duration::minutes(config().await.token_ttl_minutes()),
+        ip_address: addr.ip().to_string(),
+        user_agent: user_agent
+            .map(|TypedHeader(user_agent)| user_agent.to_string())
+            .unwrap_or_default(),
+    };
+
+    let rotated_token = match token_repository::rotate(&state.pool, hash_token(&refresh.token), replacement)
+        .await
+        .map_err(TokenError::InfraError)?
+    {
+        RotationOutcome::Rotated(rotated_token) => rotated_token,
+        RotationOutcome::Invalid => return Err(TokenError::InvalidToken),
+        RotationOutcome::Reused => return Err(TokenError::TokenReused),
+    };
+
+    // Claims are rebuilt from the database so role and admin changes apply on refresh
+    let user = user_repository::get(&state.pool, rotated_token.user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => TokenError::InvalidToken,
+            db_error => TokenError::InfraError(db_error),
+        })?;
+
+    let roles = role_repository::get_user_roles(&state.pool, user.id)
+        .await
+        .map_err(TokenError::InfraError)?;
+
+    let (access_token, access_token_expires_at) = issue_access_token(&user, &roles, rotated_token.id)
+        .await
+        .map_err(TokenError::TokenSigningError)?;
+
+    Ok(Json(RefreshTokenResponse {
+        token,
+        expires_at: rotated_token.expires_at,
+        access_token,
+        access_token_expires_at,
+    }))
+}
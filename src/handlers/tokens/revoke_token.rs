@@ -0,0 +1,42 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domain::models::token::TokenError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::token_repository;
+use crate::infra::repositories::token_repository::UpdateTokenDb;
+use crate::state::AppState;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(token_id): PathExtractor<Uuid>,
+) -> Result<StatusCode, TokenError> {
+    let token = token_repository::get(&state.pool, token_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => TokenError::NotFound(token_id),
+            db_error => TokenError::InfraError(db_error),
+        })?;
+
+    if !auth.is_self(token.user_id) && !auth.can("sessions:revoke") {
+        return Err(TokenError::NotFound(token_id));
+    }
+
+    if token.revoked_at.is_none() {
+        let changes = UpdateTokenDb {
+            expires_at: None,
+            revoked_at: Some(Utc::now()),
+            replaced_by: None,
+        };
+
+        token_repository::update(&state.pool, token.id, changes)
+            .await
+            .map_err(TokenError::InfraError)?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,46 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+
+use crate::domain::models::user::UserError;
+use crate::domain::services::password_hashing::hash_password;
+use crate::domain::services::password_policy::enforce_password_policy;
+use crate::handlers::tokens::hash_token;
+use crate::handlers::users::ConfirmPasswordResetRequest;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::{password_reset_repository, user_repository};
+use crate::utils::JsonExtractor;
+use crate::AppState;
+
+pub async fn confirm_password_reset(
+    State(state): State<AppState>,
+    JsonExtractor(request): JsonExtractor<ConfirmPasswordResetRequest>,
+) -> Result<StatusCode, UserError> {
+    let token_hash = hash_token(&request.token);
+    let user_id = password_reset_repository::find_valid(&state.pool, token_hash.clone())
+        .await
+        .map_err(UserError::InfraError)?
+        .ok_or(UserError::InvalidResetToken)?;
+
+    let user = user_repository::get(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::InvalidResetToken,
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    // Checked and hashed before using the token, so a rejected password or a busy
+    // hashing pool does not burn it
+    enforce_password_policy(&state, &request.password, &user.username, &user.email).await?;
+    let password_hash = hash_password(&state, &request.password).await?;
+
+    // Fails if the token was used concurrently in the meantime
+    if password_reset_repository::reset_password(&state.pool, token_hash, password_hash)
+        .await
+        .map_err(UserError::InfraError)?
+        != Some(user_id)
+    {
+        return Err(UserError::InvalidResetToken);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,61 @@
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::handlers::tokens::hash_token;
+use crate::handlers::users::{ConfirmTotpRequest, RecoveryCodesResponse};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::mfa_repository;
+use crate::utils::totp::{decrypt_secret, generate_recovery_codes, normalize_recovery_code, verify_code};
+use crate::utils::{AuthenticatedUser, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+// Activates TOTP once the user proves their authenticator works, recovery codes are only shown here
+pub async fn confirm_totp(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<ConfirmTotpRequest>,
+) -> Result<Json<RecoveryCodesResponse>, UserError> {
+    if auth.user_id != user_id || auth.api_key_id.is_some() {
+        return Err(UserError::Forbidden);
+    }
+
+    let totp = mfa_repository::get_totp(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::NotFound(user_id),
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    if totp.totp_enabled_at.is_some() {
+        return Err(UserError::MfaAlreadyEnabled);
+    }
+
+    let encrypted_secret = totp.totp_secret.ok_or(UserError::MfaEnrollmentNotStarted)?;
+    let secret = decrypt_secret(&encrypted_secret)
+        .await
+        .ok_or(UserError::InternalServerError)?;
+
+    let step = verify_code(&secret, &request.code, Utc::now().timestamp())
+        .ok_or(UserError::InvalidMfaCode)?;
+
+    let recovery_codes = generate_recovery_codes();
+    let recovery_code_hashes = recovery_codes
+        .iter()
+        .map(|code| hash_token(&normalize_recovery_code(code)))
+        .collect();
+
+    if !mfa_repository::enable_totp(&state.pool, user_id, step, recovery_code_hashes)
+        .await
+        .map_err(UserError::InfraError)?
+    {
+        return Err(UserError::MfaAlreadyEnabled);
+    }
+
+    tracing::info!(target: "security", user_id = %user_id, "Two-factor authentication enabled");
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
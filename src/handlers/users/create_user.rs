@@ -2,30 +2,21 @@ use axum::extract::State;
 use axum::Json;
 
 use crate::domain::models::user::UserError;
-use crate::handlers::users::{CreatUserRequest, UserResponse};
+use crate::domain::services::email_verification::send_verification_email;
+use crate::domain::services::password_hashing::hash_password;
+use crate::domain::services::password_policy::enforce_password_policy;
+use crate::handlers::users::{adapt_user_write_error, CreatUserRequest, UserResponse};
 use crate::infra::repositories::user_repository;
 use crate::utils::JsonExtractor;
 use crate::AppState;
 
-use argon2::{
-    password_hash::{
-        rand_core::OsRng,
-        PasswordHasher, 
-        SaltString
-    },
-    Argon2
-};
-
-
-
 pub async fn create_user(
     State(state): State<AppState>,
     JsonExtractor(new_user): JsonExtractor<CreatUserRequest>,
 ) -> Result<Json<UserResponse>, UserError> {
+    enforce_password_policy(&state, &new_user.password, &new_user.username, &new_user.email).await?;
 
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let hashed_password = argon2.hash_password(new_user.password.as_bytes(), &salt)?.to_string();
+    let hashed_password = hash_password(&state, &new_user.password).await?;
 
     let new_user_db = user_repository::NewUserDb {
         email: new_user.email,
@@ -36,12 +27,20 @@ pub async fn create_user(
 
     let created_user = user_repository::insert(&state.pool, new_user_db)
         .await
-        .map_err(UserError::InfraError)?;
+        .map_err(adapt_user_write_error)?;
+
+    // Signing up must not fail because the verification email could not be issued
+    if let Err(err) = send_verification_email(&state, &created_user).await {
+        tracing::error!("Failed to issue verification email: {}", err);
+    }
 
     let user_response = UserResponse {
         id: created_user.id,
         username: created_user.username,
         email: created_user.email,
+        is_admin: created_user.is_admin,
+        email_verified_at: created_user.email_verified_at,
+        mfa_enabled: created_user.totp_enabled_at.is_some(),
         created_at: created_user.created_at,
     };
 
@@ -0,0 +1,61 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::domain::services::mfa::verify_throttled_second_factor;
+use crate::handlers::users::DisableTotpRequest;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::{mfa_repository, user_repository};
+use crate::utils::{AuthenticatedUser, JsonExtractor, PathExtractor};
+use crate::AppState;
+
+// Users need a valid code to turn their own second factor off, administrators
+// with `users:write` can reset it for users who lost their authenticator
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<DisableTotpRequest>,
+) -> Result<StatusCode, UserError> {
+    if auth.api_key_id.is_some() {
+        return Err(UserError::Forbidden);
+    }
+    auth.ensure_self_or(user_id, "users:write")?;
+
+    let totp = mfa_repository::get_totp(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::NotFound(user_id),
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    if totp.totp_secret.is_none() {
+        return Err(UserError::MfaNotEnabled);
+    }
+
+    // A pending enrollment can be abandoned without a code
+    if auth.user_id == user_id && totp.totp_enabled_at.is_some() {
+        let code = request.code.ok_or(UserError::InvalidMfaCode)?;
+        let user = user_repository::get(&state.pool, user_id)
+            .await
+            .map_err(UserError::InfraError)?;
+        verify_throttled_second_factor(&state, &user, &addr.ip().to_string(), &code).await?;
+    }
+
+    mfa_repository::disable_totp(&state.pool, user_id)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    tracing::info!(
+        target: "security",
+        actor_id = %auth.user_id,
+        user_id = %user_id,
+        "Two-factor authentication disabled"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
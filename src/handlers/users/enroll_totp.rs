@@ -0,0 +1,47 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::handlers::users::TotpEnrollmentResponse;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::{mfa_repository, user_repository};
+use crate::utils::totp::{encrypt_secret, generate_secret, provisioning};
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+// Starts enrollment with a fresh secret, TOTP stays inactive until confirmed with a first code
+pub async fn enroll_totp(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+) -> Result<Json<TotpEnrollmentResponse>, UserError> {
+    // Second factors are personal, API keys cannot manage them either
+    if auth.user_id != user_id || auth.api_key_id.is_some() {
+        return Err(UserError::Forbidden);
+    }
+
+    let user = user_repository::get(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::NotFound(user_id),
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(UserError::MfaAlreadyEnabled);
+    }
+
+    let secret = generate_secret();
+    let encrypted_secret = encrypt_secret(&secret)
+        .await
+        .map_err(UserError::SecretEncryptionError)?;
+
+    mfa_repository::set_pending_totp_secret(&state.pool, user_id, encrypted_secret)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    let (secret, otpauth_uri) = provisioning(&secret, &user.username).await;
+
+    Ok(Json(TotpEnrollmentResponse { secret, otpauth_uri }))
+}
@@ -6,19 +6,22 @@ use crate::domain::models::user::{UserError, UserModel};
 use crate::handlers::users::UserResponse;
 use crate::infra::errors::InfraError;
 use crate::infra::repositories::user_repository;
-use crate::utils::PathExtractor;
+use crate::utils::{AuthenticatedUser, PathExtractor};
 use crate::AppState;
 
 pub async fn get_user(
     State(state): State<AppState>,
+    auth: AuthenticatedUser,
     PathExtractor(post_id): PathExtractor<Uuid>,
 ) -> Result<Json<UserResponse>, UserError> {
+    auth.ensure_self_or(post_id, "users:read")?;
+
     let user =
         user_repository::get(&state.pool, post_id)
             .await
             .map_err(|db_error| match db_error {
-                InfraError::InternalServerError => UserError::InternalServerError,
                 InfraError::NotFound => UserError::NotFound(post_id),
+                db_error => UserError::InfraError(db_error),
             })?;
 
     Ok(Json(adapt_user_to_user_response(user)))
@@ -29,6 +32,9 @@ fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
         id: user.id,
         email: user.email,
         username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
         created_at: user.created_at,
     }
 }
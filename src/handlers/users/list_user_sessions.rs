@@ -0,0 +1,85 @@
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domain::models::pagination::SortDirection;
+use crate::domain::models::token::TokenModel;
+use crate::domain::models::user::UserError;
+use crate::handlers::users::{ListSessionsQuery, ListSessionsResponse, SessionResponse};
+use crate::infra::repositories::token_repository::{get_all, TokenSortField, TokensFilter};
+use crate::utils::{AuthenticatedUser, PathExtractor, QueryExtractor};
+use crate::AppState;
+
+pub async fn list_user_sessions(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+    QueryExtractor(params): QueryExtractor<ListSessionsQuery>,
+) -> Result<Json<ListSessionsResponse>, UserError> {
+    auth.ensure_self_or(user_id, "sessions:read")?;
+
+    let filter = TokensFilter {
+        user_id: Some(user_id),
+        ip_address: None,
+        user_agent: None,
+        expires_at: Some(Utc::now()),
+        get_chained_tokens: Some(false),
+        revoked: Some(false),
+        limit: params.limit,
+        cursor: params.cursor,
+        sort: TokenSortField::CreatedAt,
+        direction: SortDirection::Desc,
+        include_total: false,
+    };
+
+    let tokens = get_all(&state.pool, filter)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    let sessions = tokens
+        .items
+        .into_iter()
+        .map(|token| adapt_token_to_session_response(token, auth.session_id))
+        .collect();
+
+    Ok(Json(ListSessionsResponse {
+        sessions,
+        next_cursor: tokens.next_cursor,
+    }))
+}
+
+fn adapt_token_to_session_response(token: TokenModel, current_token_id: Option<Uuid>) -> SessionResponse {
+    let (browser, os) = parse_user_agent(&token.user_agent);
+
+    SessionResponse {
+        id: token.id,
+        ip_address: token.ip_address,
+        user_agent: token.user_agent,
+        browser,
+        os,
+        created_at: token.created_at,
+        expires_at: token.expires_at,
+        current: Some(token.id) == current_token_id,
+    }
+}
+
+// Extracts a human readable browser and OS from the raw user agent, if recognized
+fn parse_user_agent(user_agent: &str) -> (Option<String>, Option<String>) {
+    match woothee::parser::Parser::new().parse(user_agent) {
+        Some(parsed) => {
+            let browser = match (parsed.name, parsed.version) {
+                ("UNKNOWN", _) => None,
+                (name, "UNKNOWN") | (name, "") => Some(name.to_string()),
+                (name, version) => Some(format!("{} {}", name, version)),
+            };
+            let os = match (parsed.os, parsed.os_version.as_ref()) {
+                ("UNKNOWN", _) => None,
+                (os, "UNKNOWN") | (os, "") => Some(os.to_string()),
+                (os, version) => Some(format!("{} {}", os, version)),
+            };
+            (browser, os)
+        }
+        None => (None, None),
+    }
+}
@@ -1,19 +1,23 @@
 use axum::extract::{State};
 use axum::Json;
 
+use crate::domain::models::pagination::Page;
 use crate::domain::models::user::{UserError, UserModel};
 use crate::handlers::users::{ListUsersResponse, UserResponse};
 use crate::infra::repositories::user_repository::{get_all, UsersFilter};
 use crate::AppState;
-use crate::utils::JsonExtractor;
+use crate::utils::{AuthenticatedUser, QueryExtractor};
 
 pub async fn list_users(
     State(state): State<AppState>,
-    JsonExtractor(params): JsonExtractor<UsersFilter>,
+    auth: AuthenticatedUser,
+    QueryExtractor(params): QueryExtractor<UsersFilter>,
 ) -> Result<Json<ListUsersResponse>, UserError> {
+    auth.require("users:read")?;
+
     let users = get_all(&state.pool, params)
         .await
-        .map_err(|_| UserError::InternalServerError)?;
+        .map_err(UserError::InfraError)?;
 
     Ok(Json(adapt_users_to_list_users_response(users)))
 }
@@ -23,15 +27,20 @@ fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
         id: user.id,
         email: user.email,
         username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
         created_at: user.created_at
     }
 }
 
-fn adapt_users_to_list_users_response(users: Vec<UserModel>) -> ListUsersResponse {
+fn adapt_users_to_list_users_response(page: Page<UserModel>) -> ListUsersResponse {
     let users_response: Vec<UserResponse> =
-        users.into_iter().map(adapt_user_to_user_response).collect();
+        page.items.into_iter().map(adapt_user_to_user_response).collect();
 
     ListUsersResponse {
         users: users_response,
+        next_cursor: page.next_cursor,
+        total: page.total,
     }
 }
@@ -1,41 +1,125 @@
-use axum::extract::State;
-use axum::Json;
+use std::net::SocketAddr;
 
+use axum::extract::{ConnectInfo, State};
+use axum::Json;
+use axum_extra::TypedHeader;
+use chrono::{Duration, Utc};
+use headers::UserAgent;
+use tokio::sync::OnceCell;
 
+use crate::config::config;
 use crate::domain::models::user::{UserError, UserModel};
-use crate::handlers::users::{LoginUserRequest, UserResponse};
+use crate::domain::services::login_throttle::{clear_account_failures, ensure_login_allowed, record_login_failure};
+use crate::domain::services::password_hashing::{hash_password, verify_password, PasswordVerification};
+use crate::domain::services::sessions::issue_session;
+use crate::handlers::tokens::{generate_token, hash_token};
+use crate::handlers::users::{LoginResponse, LoginUserRequest, LoginUserResponse, MfaChallengeResponse, UserResponse};
 use crate::infra::errors::InfraError;
-use crate::infra::repositories::user_repository;
+use crate::infra::repositories::{mfa_repository, user_repository};
 use crate::utils::{JsonExtractor};
 use crate::AppState;
 
-
-use argon2::{password_hash::{
-    PasswordHash,
-}, Argon2, PasswordVerifier};
-
-
 pub async fn login_user(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    user_agent: Option<TypedHeader<UserAgent>>,
     JsonExtractor(login_user): JsonExtractor<LoginUserRequest>,
-) -> Result<Json<UserResponse>, UserError> {
+) -> Result<Json<LoginResponse>, UserError> {
+    let ip_address = addr.ip().to_string();
+    ensure_login_allowed(&state, &login_user.username, &ip_address).await?;
+
     let user = user_repository::find_by_username(&state.pool, login_user.username.clone())
         .await
         .map_err(|db_error| match db_error {
-            InfraError::InternalServerError => UserError::InternalServerError,
-            InfraError::NotFound => UserError::InvalidCredentials(login_user.username.clone()),
-        })?
-        .ok_or_else(|| UserError::InvalidCredentials(login_user.username.clone()))?;
-
-    let parsed_hash = PasswordHash::new(&user.password_hash)
-        .map_err(|_| UserError::InvalidCredentials(login_user.username.clone()))?;
-
-    let argon2 = Argon2::default();
-    argon2
-        .verify_password(login_user.password.as_bytes(), &parsed_hash)
-        .map_err(|_| UserError::InvalidCredentials(login_user.username.clone()))?;
-    
-    Ok(Json(adapt_user_to_user_response(user)))
+            InfraError::NotFound => UserError::InvalidCredentials,
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    // Unknown usernames still pay for a hash verification so timing does not reveal which accounts exist
+    let verification = match &user {
+        Some(user) => verify_password(&state, &login_user.password, &user.password_hash).await?,
+        None => {
+            verify_password(&state, &login_user.password, dummy_password_hash(&state).await?).await?;
+            PasswordVerification::Invalid
+        }
+    };
+
+    let (Some(user), PasswordVerification::Valid { needs_rehash }) = (user, verification) else {
+        record_login_failure(&state, &login_user.username, &ip_address).await?;
+        return Err(UserError::InvalidCredentials);
+    };
+
+    // The plain password is only known here, so hashes from older parameters are upgraded now.
+    // Failing to do so must not prevent the login, it is retried on the next one.
+    if needs_rehash {
+        if let Err(err) = rehash_password(&state, &user, &login_user.password).await {
+            tracing::error!(user_id = %user.id, "Failed to upgrade password hash: {:?}", err);
+        }
+    }
+
+    if config().await.require_email_verification() && user.email_verified_at.is_none() {
+        return Err(UserError::EmailNotVerified);
+    }
+
+    // Failures are only forgotten once the second factor, if any, has been checked as well
+    if user.totp_enabled_at.is_none() {
+        clear_account_failures(&state, &login_user.username).await?;
+    }
+
+    let user_agent = user_agent
+        .map(|TypedHeader(user_agent)| user_agent.to_string())
+        .unwrap_or_default();
+
+    // With a second factor enabled the password alone only yields a short-lived challenge
+    if user.totp_enabled_at.is_some() {
+        let challenge_token = generate_token();
+        let new_challenge_db = mfa_repository::NewMfaChallengeDb {
+            user_id: user.id,
+            challenge_hash: hash_token(&challenge_token),
+            expires_at: Utc::now() + Duration::minutes(config().await.mfa_challenge_ttl_minutes()),
+        };
+
+        let challenge = mfa_repository::insert_challenge(&state.pool, new_challenge_db)
+            .await
+            .map_err(UserError::InfraError)?;
+
+        return Ok(Json(LoginResponse::MfaRequired(MfaChallengeResponse {
+            mfa_required: true,
+            challenge_token,
+            expires_at: challenge.expires_at,
+        })));
+    }
+
+    let session = issue_session(&state, &user, ip_address, user_agent).await?;
+
+    Ok(Json(LoginResponse::Authenticated(LoginUserResponse {
+        user: adapt_user_to_user_response(user),
+        token: session.token,
+        expires_at: session.expires_at,
+        access_token: session.access_token,
+        access_token_expires_at: session.access_token_expires_at,
+    })))
+}
+
+// Only the hash is written, other fields may have changed since the user was read
+async fn rehash_password(state: &AppState, user: &UserModel, password: &str) -> Result<(), UserError> {
+    let new_hash = hash_password(state, password).await?;
+
+    user_repository::update_password_hash(&state.pool, user.id, user.password_hash.clone(), new_hash)
+        .await
+        .map_err(UserError::InfraError)?;
+    Ok(())
+}
+
+// Hash of a random password, computed once with the current parameters so that it
+// costs as much to verify as a real one, that no submitted password can match.
+// A failed computation is not cached and is retried on the next unknown username.
+async fn dummy_password_hash(state: &AppState) -> Result<&'static str, UserError> {
+    static DUMMY_PASSWORD_HASH: OnceCell<String> = OnceCell::const_new();
+    DUMMY_PASSWORD_HASH
+        .get_or_try_init(|| async { hash_password(state, &generate_token()).await })
+        .await
+        .map(String::as_str)
 }
 
 fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
@@ -43,6 +127,9 @@ fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
         id: user.id,
         email: user.email,
         username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
         created_at: user.created_at,
     }
-}
\ No newline at end of file
+}
@@ -1,51 +1,279 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
+use crate::domain::models::pagination::{Cursor, SortDirection, MAX_PAGE_LIMIT};
+use crate::domain::models::user::UserError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::token_repository::{validate_token_cursor, TokenSortField};
+use crate::infra::repositories::user_repository::{EMAIL_UNIQUE_INDEX, USERNAME_UNIQUE_INDEX};
+use crate::utils::validation::USERNAME_REGEX;
+
+pub use confirm_password_reset::confirm_password_reset;
+pub use confirm_totp::confirm_totp;
 pub use create_user::create_user;
+pub use disable_totp::disable_totp;
+pub use enroll_totp::enroll_totp;
 pub use get_user::get_user;
 pub use list_users::list_users;
 pub use patch_user::patch_user;
 pub use login_user::login_user;
+pub use list_user_sessions::list_user_sessions;
+pub use request_password_reset::request_password_reset;
+pub use resend_verification_email::resend_verification_email;
+pub use search_users::search_users;
+pub use revoke_user_session::revoke_user_session;
+pub use revoke_user_sessions::revoke_user_sessions;
+pub use set_user_admin::set_user_admin;
+pub use unlock_user::unlock_user;
+pub use verify_email::verify_email;
+pub use verify_login_mfa::verify_login_mfa;
 
 
 mod create_user;
 mod get_user;
 mod list_users;
+mod search_users;
 
 mod patch_user;
 
 mod login_user;
+mod verify_login_mfa;
+
+mod confirm_totp;
+mod disable_totp;
+mod enroll_totp;
+
+mod confirm_password_reset;
+mod request_password_reset;
+
+mod resend_verification_email;
+mod verify_email;
 
-#[derive(Debug, Deserialize)]
+mod list_user_sessions;
+mod revoke_user_session;
+mod revoke_user_sessions;
+
+mod set_user_admin;
+mod unlock_user;
+
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreatUserRequest {
+    #[validate(length(min = 3, max = 32), regex(path = *USERNAME_REGEX))]
     username: String,
+    #[validate(email, length(max = 254))]
     email: String,
+    // Strength rules come from the configurable password policy
+    #[validate(length(min = 1, max = 128))]
     password: String,
 }
 
-#[derive(Debug, Deserialize)]
+// Same rules as on creation, absent fields are left unchanged
+#[derive(Debug, Deserialize, Validate)]
 pub struct PatchUserRequest {
+    #[validate(length(min = 3, max = 32), regex(path = *USERNAME_REGEX))]
     pub username: Option<String>,
+    #[validate(email, length(max = 254))]
     pub email: Option<String>,
+    #[validate(length(min = 1, max = 128))]
     pub password: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+// No strength rule here, accounts created before the policy must still be able to log in
+#[derive(Debug, Deserialize, Validate)]
 pub struct LoginUserRequest {
+    #[validate(length(min = 1, max = 254))]
     pub username: String,
+    #[validate(length(min = 1, max = 1024))]
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyLoginMfaRequest {
+    #[validate(length(min = 1, max = 256))]
+    pub challenge_token: String,
+    // Either a TOTP code or a recovery code
+    #[validate(length(min = 1, max = 32))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmTotpRequest {
+    #[validate(length(equal = 6))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DisableTotpRequest {
+    #[validate(length(min = 1, max = 32))]
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PasswordResetRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmPasswordResetRequest {
+    #[validate(length(min = 1, max = 256))]
+    pub token: String,
+    #[validate(length(min = 1, max = 128))]
     pub password: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyEmailQuery {
+    #[validate(length(min = 1, max = 256))]
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = validate_list_sessions_query))]
+pub struct ListSessionsQuery {
+    #[validate(range(min = 1, max = MAX_PAGE_LIMIT))]
+    pub limit: Option<i64>,
+    // `next_cursor` of the previous page
+    pub cursor: Option<Cursor>,
+}
+
+// Sessions are always listed newest first
+fn validate_list_sessions_query(query: &ListSessionsQuery) -> Result<(), validator::ValidationError> {
+    match &query.cursor {
+        Some(cursor) => validate_token_cursor(cursor, TokenSortField::CreatedAt, SortDirection::Desc),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResendVerificationEmailRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetUserAdminRequest {
+    pub is_admin: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserResponse {
     id: Uuid,
     username: String,
     email: String,
+    is_admin: bool,
+    email_verified_at: Option<DateTime<Utc>>,
+    mfa_enabled: bool,
     created_at: NaiveDate,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeResponse {
+    mfa_required: bool,
+    // Exchanged with a second factor code at /v1/users/login/mfa
+    challenge_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(LoginUserResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpEnrollmentResponse {
+    // Base32 secret for manual entry in authenticator apps
+    secret: String,
+    otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginUserResponse {
+    user: UserResponse,
+    // Long-lived refresh token, only accepted by /v1/tokens/refresh
+    token: String,
+    expires_at: DateTime<Utc>,
+    // Short-lived signed token to authenticate every other request
+    access_token: String,
+    access_token_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionResponse {
+    id: Uuid,
+    ip_address: String,
+    user_agent: String,
+    browser: Option<String>,
+    os: Option<String>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    current: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListSessionsResponse {
+    sessions: Vec<SessionResponse>,
+    // None on the last page
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevokeSessionsResponse {
+    revoked: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListUsersResponse {
     users: Vec<UserResponse>,
+    // Pass it back as `cursor` to get the next page, null on the last page
+    next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<i64>,
+}
+
+// Fields containing a word of the query, with the matching parts in <mark> tags
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserSearchHighlights {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserSearchResult {
+    user: UserResponse,
+    // From 0 to 1, results are sorted on it
+    score: f32,
+    highlights: UserSearchHighlights,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchUsersResponse {
+    results: Vec<UserSearchResult>,
+    next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<i64>,
+}
+
+// Turns unique violations on users into a conflict naming the taken field
+pub fn adapt_user_write_error(db_error: InfraError) -> UserError {
+    match &db_error {
+        InfraError::UniqueViolation { constraint: Some(constraint), .. } if constraint == USERNAME_UNIQUE_INDEX => {
+            UserError::Conflict("username")
+        }
+        InfraError::UniqueViolation { constraint: Some(constraint), .. } if constraint == EMAIL_UNIQUE_INDEX => {
+            UserError::Conflict("email")
+        }
+        _ => UserError::InfraError(db_error),
+    }
 }
@@ -4,34 +4,34 @@ use axum::Json;
 use uuid::Uuid;
 
 use crate::domain::models::user::{UserError, UserModel};
-use crate::handlers::users::{PatchUserRequest, UserResponse};
+use crate::domain::services::email_verification::send_verification_email;
+use crate::domain::services::password_hashing::hash_password;
+use crate::domain::services::password_policy::enforce_password_policy;
+use crate::handlers::users::{adapt_user_write_error, PatchUserRequest, UserResponse};
 use crate::infra::errors::InfraError;
-use crate::infra::repositories::user_repository;
-use crate::utils::{JsonExtractor, PathExtractor};
+use crate::infra::repositories::{token_repository, user_repository};
+use crate::utils::{AuthenticatedUser, JsonExtractor, PathExtractor};
 use crate::AppState;
 use crate::infra::repositories::user_repository::UpdateUserDb;
 
-
-use argon2::{
-    password_hash::{
-        rand_core::OsRng,
-        PasswordHasher,
-        SaltString
-    },
-    Argon2
-};
-
-
 pub async fn patch_user(
     State(state): State<AppState>,
+    auth: AuthenticatedUser,
     PathExtractor(user_id): PathExtractor<Uuid>,
     JsonExtractor(patch_user): JsonExtractor<PatchUserRequest>,
 ) -> Result<Json<UserResponse>, UserError> {
+    auth.ensure_self_or(user_id, "users:write")?;
+
+    // Credentials are only changed from a session, whatever the scopes of an API key
+    if auth.api_key_id.is_some() && (patch_user.password.is_some() || patch_user.email.is_some()) {
+        return Err(UserError::Forbidden);
+    }
+
     let mut user = user_repository::get(&state.pool, user_id)
         .await
         .map_err(|db_error| match db_error {
-            InfraError::InternalServerError => UserError::InternalServerError,
             InfraError::NotFound => UserError::NotFound(user_id),
+            db_error => UserError::InfraError(db_error),
         })?;
 
 
@@ -40,20 +40,38 @@ pub async fn patch_user(
         user.username = username
     }
 
+    // A new address has to be verified again
+    let email_changed = patch_user.email.as_ref().is_some_and(|email| *email != user.email);
     if let Some(email) = patch_user.email {
         user.email = email
     }
+    if email_changed {
+        user.email_verified_at = None;
+    }
 
+    let password_changed = patch_user.password.is_some();
     if let Some(password) = patch_user.password {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        let hashed_password = argon2.hash_password(password.as_bytes(), &salt)?.to_string();
-        user.password_hash = hashed_password;
+        enforce_password_policy(&state, &password, &user.username, &user.email).await?;
+
+        user.password_hash = hash_password(&state, &password).await?;
     }
     let update_user = adapt_user_to_user_patch(user);
     let updated_user = user_repository::update(&state.pool, user_id, update_user)
         .await
-        .map_err(|_| UserError::InternalServerError)?;
+        .map_err(adapt_user_write_error)?;
+
+    // A new password invalidates every existing session
+    if password_changed {
+        token_repository::revoke_all_for_user(&state.pool, user_id)
+            .await
+            .map_err(UserError::InfraError)?;
+    }
+
+    if email_changed {
+        if let Err(err) = send_verification_email(&state, &updated_user).await {
+            tracing::error!("Failed to issue verification email: {}", err);
+        }
+    }
 
     Ok(Json(adapt_user_to_user_response(updated_user)))
 }
@@ -63,6 +81,9 @@ fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
         id: user.id,
         email: user.email,
         username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
         created_at: user.created_at,
     }
 }
@@ -72,6 +93,6 @@ fn adapt_user_to_user_patch(user: UserModel) -> UpdateUserDb {
         email: user.email,
         username: user.username,
         password_hash: user.password_hash,
-        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
     }
 }
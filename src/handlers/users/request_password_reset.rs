@@ -0,0 +1,70 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+
+use crate::config::config;
+use crate::handlers::tokens::{generate_token, hash_token};
+use crate::handlers::users::PasswordResetRequest;
+use crate::infra::mailer::EmailMessage;
+use crate::infra::repositories::password_reset_repository::NewPasswordResetTokenDb;
+use crate::infra::repositories::{password_reset_repository, user_repository};
+use crate::utils::JsonExtractor;
+use crate::AppState;
+
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    JsonExtractor(request): JsonExtractor<PasswordResetRequest>,
+) -> StatusCode {
+    // The work happens in the background and the answer is always the same,
+    // so neither the body nor the timing reveal whether the email is registered
+    tokio::spawn(async move {
+        send_password_reset(&state, request.email, addr.ip().to_string()).await;
+    });
+
+    StatusCode::ACCEPTED
+}
+
+async fn send_password_reset(state: &AppState, email: String, ip_address: String) {
+    let user = match user_repository::find_by_email(&state.pool, email).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return,
+        Err(err) => {
+            tracing::error!("Failed to look up user for password reset: {}", err);
+            return;
+        }
+    };
+
+    let app_config = config().await;
+    let token = generate_token();
+    let new_token_db = NewPasswordResetTokenDb {
+        user_id: user.id,
+        token_hash: hash_token(&token),
+        expires_at: Utc::now() + Duration::minutes(app_config.password_reset_ttl_minutes()),
+        ip_address,
+    };
+
+    if let Err(err) = password_reset_repository::insert(&state.pool, new_token_db).await {
+        tracing::error!("Failed to store password reset token: {}", err);
+        return;
+    }
+
+    // The frontend page asks for the new password and posts it with the token to /v1/users/password-reset/confirm
+    let message = EmailMessage {
+        to: user.email,
+        subject: String::from("Reset your password"),
+        body: format!(
+            "Hello {},\n\nUse the link below to choose a new password. It expires in {} minutes.\n\n{}/password-reset?token={}\n\nIf you did not request this, you can ignore this email.",
+            user.username,
+            app_config.password_reset_ttl_minutes(),
+            app_config.frontend_url(),
+            token
+        ),
+    };
+
+    if let Err(err) = state.mailer.send(&message).await {
+        tracing::error!("Failed to send password reset email: {}", err);
+    }
+}
@@ -0,0 +1,52 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+
+use crate::config::config;
+use crate::domain::services::email_verification::send_verification_email;
+use crate::handlers::users::ResendVerificationEmailRequest;
+use crate::infra::repositories::{email_verification_repository, user_repository};
+use crate::utils::JsonExtractor;
+use crate::AppState;
+
+pub async fn resend_verification_email(
+    State(state): State<AppState>,
+    JsonExtractor(request): JsonExtractor<ResendVerificationEmailRequest>,
+) -> StatusCode {
+    // Unverified users may not be able to log in, so this is keyed by email and,
+    // like password resets, always answers the same way to avoid user enumeration
+    tokio::spawn(async move {
+        resend(&state, request.email).await;
+    });
+
+    StatusCode::ACCEPTED
+}
+
+async fn resend(state: &AppState, email: String) {
+    let user = match user_repository::find_by_email(&state.pool, email).await {
+        Ok(Some(user)) if user.email_verified_at.is_none() => user,
+        Ok(_) => return,
+        Err(err) => {
+            tracing::error!("Failed to look up user for verification email: {}", err);
+            return;
+        }
+    };
+
+    // Throttled requests are silently dropped
+    let cooldown = Duration::seconds(config().await.email_verification_resend_cooldown_seconds());
+    match email_verification_repository::latest_created_at(&state.pool, user.id).await {
+        Ok(Some(last_sent_at)) if last_sent_at + cooldown > Utc::now() => {
+            tracing::info!(user_id = %user.id, "Verification email resend throttled");
+            return;
+        }
+        Ok(_) => {}
+        Err(err) => {
+            tracing::error!("Failed to check verification email throttling: {}", err);
+            return;
+        }
+    }
+
+    if let Err(err) = send_verification_email(state, &user).await {
+        tracing::error!("Failed to issue verification email: {}", err);
+    }
+}
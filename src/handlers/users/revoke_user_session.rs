@@ -0,0 +1,45 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::token_repository;
+use crate::infra::repositories::token_repository::UpdateTokenDb;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn revoke_user_session(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor((user_id, session_id)): PathExtractor<(Uuid, Uuid)>,
+) -> Result<StatusCode, UserError> {
+    auth.ensure_self_or(user_id, "sessions:revoke")?;
+
+    let token = token_repository::get(&state.pool, session_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::SessionNotFound(session_id),
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    // Sessions of other users are reported as missing rather than forbidden
+    if token.user_id != user_id {
+        return Err(UserError::SessionNotFound(session_id));
+    }
+
+    if token.revoked_at.is_none() {
+        let changes = UpdateTokenDb {
+            expires_at: None,
+            revoked_at: Some(Utc::now()),
+            replaced_by: None,
+        };
+
+        token_repository::update(&state.pool, token.id, changes)
+            .await
+            .map_err(UserError::InfraError)?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
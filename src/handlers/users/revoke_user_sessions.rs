@@ -0,0 +1,23 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::handlers::users::RevokeSessionsResponse;
+use crate::infra::repositories::token_repository;
+use crate::utils::{AuthenticatedUser, PathExtractor};
+use crate::AppState;
+
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+) -> Result<Json<RevokeSessionsResponse>, UserError> {
+    auth.ensure_self_or(user_id, "sessions:revoke")?;
+
+    let revoked = token_repository::revoke_all_for_user(&state.pool, user_id)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
@@ -0,0 +1,113 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::config::config;
+use crate::domain::models::pagination::Page;
+use crate::domain::models::user::{UserError, UserModel, UserSearchHit};
+use crate::handlers::users::{SearchUsersResponse, UserResponse, UserSearchHighlights, UserSearchResult};
+use crate::infra::repositories::user_repository::{search, UserSearchQuery};
+use crate::AppState;
+use crate::utils::{AuthenticatedUser, QueryExtractor};
+
+pub async fn search_users(
+    State(state): State<AppState>,
+    auth: AuthenticatedUser,
+    QueryExtractor(params): QueryExtractor<UserSearchQuery>,
+) -> Result<Json<SearchUsersResponse>, UserError> {
+    auth.require("users:read")?;
+
+    let q = params.q.clone();
+    let hits = search(&state.pool, params, config().await.user_search_min_similarity())
+        .await
+        .map_err(UserError::InfraError)?;
+
+    Ok(Json(adapt_hits_to_search_users_response(hits, &q)))
+}
+
+// Wraps every literal, case-insensitive occurrence of a word of the query in <mark>
+// tags, the rest being HTML escaped. None when no word occurs as is, the user was then
+// found on similarity alone.
+fn highlight(value: &str, q: &str) -> Option<String> {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let chars: Vec<char> = value.chars().collect();
+    let mut marked = vec![false; chars.len()];
+
+    for term in q.split_whitespace() {
+        let term: Vec<char> = term.chars().map(fold).collect();
+        if term.len() > chars.len() {
+            continue;
+        }
+        for start in 0..=chars.len() - term.len() {
+            let window = &chars[start..start + term.len()];
+            if window.iter().zip(&term).all(|(c, t)| fold(*c) == *t) {
+                marked[start..start + term.len()].fill(true);
+            }
+        }
+    }
+
+    if !marked.contains(&true) {
+        return None;
+    }
+
+    let mut highlighted = String::with_capacity(value.len() + 16);
+    for (index, c) in chars.iter().enumerate() {
+        let was_marked = index > 0 && marked[index - 1];
+        if marked[index] && !was_marked {
+            highlighted.push_str("<mark>");
+        } else if !marked[index] && was_marked {
+            highlighted.push_str("</mark>");
+        }
+        match c {
+            '&' => highlighted.push_str("&amp;"),
+            '<' => highlighted.push_str("&lt;"),
+            '>' => highlighted.push_str("&gt;"),
+            '"' => highlighted.push_str("&quot;"),
+            '\'' => highlighted.push_str("&#39;"),
+            _ => highlighted.push(*c),
+        }
+    }
+    if marked.last() == Some(&true) {
+        highlighted.push_str("</mark>");
+    }
+
+    Some(highlighted)
+}
+
+fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
+    UserResponse {
+        id: user.id,
+        email: user.email,
+        username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
+        created_at: user.created_at
+    }
+}
+
+fn adapt_hit_to_user_search_result(hit: UserSearchHit, q: &str) -> UserSearchResult {
+    let highlights = UserSearchHighlights {
+        username: highlight(&hit.user.username, q),
+        email: highlight(&hit.user.email, q),
+    };
+
+    UserSearchResult {
+        user: adapt_user_to_user_response(hit.user),
+        score: hit.score,
+        highlights,
+    }
+}
+
+fn adapt_hits_to_search_users_response(page: Page<UserSearchHit>, q: &str) -> SearchUsersResponse {
+    let results: Vec<UserSearchResult> = page
+        .items
+        .into_iter()
+        .map(|hit| adapt_hit_to_user_search_result(hit, q))
+        .collect();
+
+    SearchUsersResponse {
+        results,
+        next_cursor: page.next_cursor,
+        total: page.total,
+    }
+}
@@ -0,0 +1,60 @@
+use axum::extract::State;
+use axum::Json;
+use uuid::Uuid;
+
+use crate::domain::models::user::{UserError, UserModel};
+use crate::handlers::users::{SetUserAdminRequest, UserResponse};
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::{role_repository, user_repository};
+use crate::utils::{JsonExtractor, PathExtractor, RequireAdmin};
+use crate::AppState;
+
+pub async fn set_user_admin(
+    State(state): State<AppState>,
+    RequireAdmin(admin): RequireAdmin,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+    JsonExtractor(request): JsonExtractor<SetUserAdminRequest>,
+) -> Result<Json<UserResponse>, UserError> {
+    // Prevents administrators from locking themselves out
+    if admin.user_id == user_id && !request.is_admin {
+        return Err(UserError::Forbidden);
+    }
+
+    user_repository::get(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::NotFound(user_id),
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    // Administrator rights come from the admin role, the flag follows it
+    role_repository::set_admin(&state.pool, user_id, request.is_admin)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    let updated_user = user_repository::get(&state.pool, user_id)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    tracing::info!(
+        target: "security",
+        admin_id = %admin.user_id,
+        user_id = %user_id,
+        is_admin = request.is_admin,
+        "Administrator flag changed"
+    );
+
+    Ok(Json(adapt_user_to_user_response(updated_user)))
+}
+
+fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
+    UserResponse {
+        id: user.id,
+        email: user.email,
+        username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
+        created_at: user.created_at,
+    }
+}
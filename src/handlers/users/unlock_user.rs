@@ -0,0 +1,35 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::domain::services::login_throttle::clear_account_failures;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::user_repository;
+use crate::utils::{PathExtractor, RequireAdmin};
+use crate::AppState;
+
+// Lifts a lockout and forgets the failed logins of the account
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    RequireAdmin(admin): RequireAdmin,
+    PathExtractor(user_id): PathExtractor<Uuid>,
+) -> Result<StatusCode, UserError> {
+    let user = user_repository::get(&state.pool, user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::NotFound(user_id),
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    clear_account_failures(&state, &user.username).await?;
+
+    tracing::info!(
+        target: "security",
+        admin_id = %admin.user_id,
+        user_id = %user_id,
+        "Account unlocked"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
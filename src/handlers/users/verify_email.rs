@@ -0,0 +1,21 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+
+use crate::domain::models::user::UserError;
+use crate::handlers::tokens::hash_token;
+use crate::handlers::users::VerifyEmailQuery;
+use crate::infra::repositories::email_verification_repository;
+use crate::utils::QueryExtractor;
+use crate::AppState;
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    QueryExtractor(query): QueryExtractor<VerifyEmailQuery>,
+) -> Result<StatusCode, UserError> {
+    email_verification_repository::consume(&state.pool, hash_token(&query.token))
+        .await
+        .map_err(UserError::InfraError)?
+        .ok_or(UserError::InvalidVerificationToken)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,73 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, State};
+use axum::Json;
+use axum_extra::TypedHeader;
+use headers::UserAgent;
+
+use crate::config::config;
+use crate::domain::models::user::{UserError, UserModel};
+use crate::domain::services::mfa::verify_throttled_second_factor;
+use crate::domain::services::sessions::issue_session;
+use crate::handlers::tokens::hash_token;
+use crate::handlers::users::{LoginUserResponse, UserResponse, VerifyLoginMfaRequest};
+use crate::infra::repositories::{mfa_repository, user_repository};
+use crate::utils::JsonExtractor;
+use crate::AppState;
+
+pub async fn verify_login_mfa(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    JsonExtractor(request): JsonExtractor<VerifyLoginMfaRequest>,
+) -> Result<Json<LoginUserResponse>, UserError> {
+    // Every code submitted counts against the challenge, which dies after too many attempts
+    let challenge = mfa_repository::reserve_attempt(
+        &state.pool,
+        hash_token(&request.challenge_token),
+        config().await.mfa_challenge_max_attempts(),
+    )
+    .await
+    .map_err(UserError::InfraError)?
+    .ok_or(UserError::InvalidMfaChallenge)?;
+
+    let user = user_repository::get(&state.pool, challenge.user_id)
+        .await
+        .map_err(UserError::InfraError)?;
+
+    // The challenge cap alone would reset with every new login, codes are throttled per account too
+    let ip_address = addr.ip().to_string();
+    verify_throttled_second_factor(&state, &user, &ip_address, &request.code).await?;
+
+    if !mfa_repository::consume_challenge(&state.pool, challenge.id)
+        .await
+        .map_err(UserError::InfraError)?
+    {
+        return Err(UserError::InvalidMfaChallenge);
+    }
+
+    let user_agent = user_agent
+        .map(|TypedHeader(user_agent)| user_agent.to_string())
+        .unwrap_or_default();
+    let session = issue_session(&state, &user, ip_address, user_agent).await?;
+
+    Ok(Json(LoginUserResponse {
+        user: adapt_user_to_user_response(user),
+        token: session.token,
+        expires_at: session.expires_at,
+        access_token: session.access_token,
+        access_token_expires_at: session.access_token_expires_at,
+    }))
+}
+
+fn adapt_user_to_user_response(user: UserModel) -> UserResponse {
+    UserResponse {
+        id: user.id,
+        email: user.email,
+        username: user.username,
+        is_admin: user.is_admin,
+        email_verified_at: user.email_verified_at,
+        mfa_enabled: user.totp_enabled_at.is_some(),
+        created_at: user.created_at,
+    }
+}
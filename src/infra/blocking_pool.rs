@@ -0,0 +1,95 @@
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use prometheus::IntGauge;
+use tokio::sync::oneshot;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+#[derive(Debug)]
+pub enum BlockingPoolError {
+    // Every worker is busy and the queue is full
+    Saturated,
+    // The job panicked or the pool is shutting down
+    Failed,
+}
+
+impl fmt::Display for BlockingPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockingPoolError::Saturated => write!(f, "Blocking pool queue is full"),
+            BlockingPoolError::Failed => write!(f, "Blocking pool job did not complete"),
+        }
+    }
+}
+
+// Fixed set of threads running CPU heavy jobs away from the Tokio runtime, so a burst
+// of them cannot starve other requests. At most `queue_limit` jobs wait for a thread,
+// further jobs are refused right away instead of piling up.
+pub struct BlockingPool {
+    sender: SyncSender<Job>,
+    queue_depth: IntGauge,
+}
+
+impl BlockingPool {
+    pub fn new(name: &str, workers: usize, queue_limit: usize, queue_depth: IntGauge) -> Self {
+        let (sender, receiver) = sync_channel::<Job>(queue_limit);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for index in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("{}-{}", name, index))
+                .spawn(move || run_worker(receiver))
+                .expect("Failed to spawn blocking pool worker");
+        }
+
+        BlockingPool { sender, queue_depth }
+    }
+
+    pub async fn run<F, R>(&self, job: F) -> Result<R, BlockingPoolError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (result_sender, result_receiver) = oneshot::channel();
+        let queue_depth = self.queue_depth.clone();
+        let job: Job = Box::new(move || {
+            queue_depth.dec();
+            // The caller may have gone away in the meantime, the result is then dropped
+            let _ = result_sender.send(job());
+        });
+
+        self.queue_depth.inc();
+        if let Err(err) = self.sender.try_send(job) {
+            self.queue_depth.dec();
+            return Err(match err {
+                TrySendError::Full(_) => BlockingPoolError::Saturated,
+                TrySendError::Disconnected(_) => BlockingPoolError::Failed,
+            });
+        }
+
+        result_receiver.await.map_err(|_| BlockingPoolError::Failed)
+    }
+}
+
+fn run_worker(receiver: Arc<Mutex<Receiver<Job>>>) {
+    loop {
+        // The lock is only held while waiting, never while a job runs
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        let Ok(job) = job else {
+            return;
+        };
+
+        // A panicking job drops its result sender, the caller sees `Failed`
+        if catch_unwind(AssertUnwindSafe(job)).is_err() {
+            tracing::error!("Blocking pool job panicked");
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+// Length of the SHA-1 prefix used to split hashes into ranges
+const PREFIX_LENGTH: usize = 5;
+const HASH_LENGTH: usize = 40;
+
+// SHA-1 hashes of known breached passwords, kept in memory so lookups work offline.
+//
+// Two Have I Been Pwned layouts are read, the hash being the hex SHA-1 of the password:
+// - a single file with one full `HASH:COUNT` line per password;
+// - a directory of range files as downloaded from the range API, each named after its
+//   5 character prefix (`21BD1` or `21BD1.txt`) and holding `SUFFIX:COUNT` lines.
+// Hashes are grouped by prefix like the range API does.
+#[derive(Debug, Default)]
+pub struct BreachedPasswords {
+    ranges: HashMap<String, HashSet<String>>,
+    len: usize,
+}
+
+impl BreachedPasswords {
+    // No list configured, every password is accepted
+    pub fn empty() -> Self {
+        BreachedPasswords::default()
+    }
+
+    pub async fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut breached = BreachedPasswords::empty();
+
+        if fs::metadata(path).await?.is_dir() {
+            let mut entries = fs::read_dir(path).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_type().await?.is_file() {
+                    breached.load_file(&entry.path()).await?;
+                }
+            }
+        } else {
+            breached.load_file(path).await?;
+        }
+
+        Ok(breached)
+    }
+
+    async fn load_file(&mut self, path: &Path) -> io::Result<()> {
+        // Only range files are named after a prefix, their lines hold the rest of the hash
+        let range_prefix = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| is_hex(stem, PREFIX_LENGTH))
+            .map(str::to_ascii_uppercase);
+
+        let mut lines = BufReader::new(File::open(path).await?).lines();
+        while let Some(line) = lines.next_line().await? {
+            let hash = line.split(':').next().unwrap_or_default().trim();
+            let hash = if is_hex(hash, HASH_LENGTH) {
+                hash.to_ascii_uppercase()
+            } else {
+                match &range_prefix {
+                    Some(prefix) if is_hex(hash, HASH_LENGTH - PREFIX_LENGTH) => {
+                        format!("{}{}", prefix, hash.to_ascii_uppercase())
+                    }
+                    _ => continue,
+                }
+            };
+
+            let (prefix, suffix) = hash.split_at(PREFIX_LENGTH);
+            if self
+                .ranges
+                .entry(prefix.to_string())
+                .or_default()
+                .insert(suffix.to_string())
+            {
+                self.len += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(PREFIX_LENGTH);
+        self.ranges
+            .get(prefix)
+            .is_some_and(|suffixes| suffixes.contains(suffix))
+    }
+}
+
+fn is_hex(value: &str, length: usize) -> bool {
+    value.len() == length && value.chars().all(|c| c.is_ascii_hexdigit())
+}
@@ -0,0 +1,16 @@
+use diesel::sql_types::{Bool, Float4, Text};
+use diesel::{define_sql_function, infix_operator};
+
+define_sql_function!(fn lower(x: Text) -> Text);
+
+// Changes a setting, for the current transaction only when `is_local` is set
+define_sql_function!(fn set_config(setting_name: Text, new_value: Text, is_local: Bool) -> Text);
+
+define_sql_function!(fn greatest(x: Float4, y: Float4) -> Float4);
+
+// pg_trgm: how well `x` matches some part of `y`, from 0 to 1
+define_sql_function!(fn word_similarity(x: Text, y: Text) -> Float4);
+
+// pg_trgm: word_similarity(left, right) reaches `pg_trgm.word_similarity_threshold`,
+// can be answered from a trigram index on `right`
+infix_operator!(WordSimilar, " <% ", backend: diesel::pg::Pg);
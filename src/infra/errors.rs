@@ -1,47 +1,105 @@
 use std::fmt;
 
 use deadpool_diesel::InteractError;
+use diesel::result::DatabaseErrorKind;
 
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// Storage failures with their original cause. Handlers decide how each kind is
+// surfaced to clients, the cause itself is only ever logged.
 #[derive(Debug)]
 pub enum InfraError {
-    InternalServerError,
     NotFound,
+    // `constraint` is the name of the violated constraint when Postgres reports it
+    UniqueViolation { constraint: Option<String>, source: BoxError },
+    ForeignKeyViolation { constraint: Option<String>, source: BoxError },
+    // The transaction lost a race against a concurrent one and can be retried
+    SerializationFailure(BoxError),
+    // No connection became available in time
+    PoolTimeout(BoxError),
+    // The database could not be reached or dropped the connection
+    ConnectionLost(BoxError),
+    InternalServerError(BoxError),
 }
 
 pub fn adapt_infra_error<T: Error>(error: T) -> InfraError {
-    error.as_infra_error()
+    error.into_infra_error()
 }
 
 impl fmt::Display for InfraError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             InfraError::NotFound => write!(f, "Not found"),
-            InfraError::InternalServerError => write!(f, "Internal server error"),
+            InfraError::UniqueViolation { constraint, source } => {
+                write!(f, "Unique violation on {}: {}", constraint.as_deref().unwrap_or("unknown constraint"), source)
+            }
+            InfraError::ForeignKeyViolation { constraint, source } => {
+                write!(f, "Foreign key violation on {}: {}", constraint.as_deref().unwrap_or("unknown constraint"), source)
+            }
+            InfraError::SerializationFailure(source) => write!(f, "Serialization failure: {}", source),
+            InfraError::PoolTimeout(source) => write!(f, "Connection pool timeout: {}", source),
+            InfraError::ConnectionLost(source) => write!(f, "Database connection lost: {}", source),
+            InfraError::InternalServerError(source) => write!(f, "Internal server error: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for InfraError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InfraError::NotFound => None,
+            InfraError::UniqueViolation { source, .. }
+            | InfraError::ForeignKeyViolation { source, .. }
+            | InfraError::SerializationFailure(source)
+            | InfraError::PoolTimeout(source)
+            | InfraError::ConnectionLost(source)
+            | InfraError::InternalServerError(source) => Some(source.as_ref()),
         }
     }
 }
 
 pub trait Error {
-    fn as_infra_error(&self) -> InfraError;
+    fn into_infra_error(self) -> InfraError;
 }
 
 impl Error for diesel::result::Error {
-    fn as_infra_error(&self) -> InfraError {
-        match self {
+    fn into_infra_error(self) -> InfraError {
+        match &self {
             diesel::result::Error::NotFound => InfraError::NotFound,
-            _ => InfraError::InternalServerError,
+            diesel::result::Error::DatabaseError(kind, info) => {
+                let constraint = info.constraint_name().map(String::from);
+                match kind {
+                    DatabaseErrorKind::UniqueViolation => InfraError::UniqueViolation {
+                        constraint,
+                        source: Box::new(self),
+                    },
+                    DatabaseErrorKind::ForeignKeyViolation => InfraError::ForeignKeyViolation {
+                        constraint,
+                        source: Box::new(self),
+                    },
+                    DatabaseErrorKind::SerializationFailure => InfraError::SerializationFailure(Box::new(self)),
+                    DatabaseErrorKind::ClosedConnection => InfraError::ConnectionLost(Box::new(self)),
+                    _ => InfraError::InternalServerError(Box::new(self)),
+                }
+            }
+            _ => InfraError::InternalServerError(Box::new(self)),
         }
     }
 }
 
 impl Error for deadpool_diesel::PoolError {
-    fn as_infra_error(&self) -> InfraError {
-        InfraError::InternalServerError
+    fn into_infra_error(self) -> InfraError {
+        match self {
+            deadpool_diesel::PoolError::Timeout(_) => InfraError::PoolTimeout(Box::new(self)),
+            deadpool_diesel::PoolError::Backend(_) => InfraError::ConnectionLost(Box::new(self)),
+            _ => InfraError::InternalServerError(Box::new(self)),
+        }
     }
 }
 
 impl Error for InteractError {
-    fn as_infra_error(&self) -> InfraError {
-        InfraError::InternalServerError
+    fn into_infra_error(self) -> InfraError {
+        // Panic payloads are not `Sync`, only their description is kept
+        InfraError::InternalServerError(self.to_string().into())
     }
 }
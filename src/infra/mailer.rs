@@ -0,0 +1,95 @@
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::Utc;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::Config;
+
+#[derive(Clone, Debug)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub enum MailerError {
+    Io(std::io::Error),
+}
+
+impl fmt::Display for MailerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MailerError::Io(err) => write!(f, "Failed to write email: {}", err),
+        }
+    }
+}
+
+pub type MailerFuture<'a> = Pin<Box<dyn Future<Output = Result<(), MailerError>> + Send + 'a>>;
+
+// Delivery backend for outgoing emails, selected through the `MAILER` setting
+pub trait Mailer: Send + Sync {
+    fn send<'a>(&'a self, message: &'a EmailMessage) -> MailerFuture<'a>;
+}
+
+// Writes emails to the application logs, meant for local development
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send<'a>(&'a self, message: &'a EmailMessage) -> MailerFuture<'a> {
+        Box::pin(async move {
+            tracing::info!(
+                to = %message.to,
+                subject = %message.subject,
+                "Outgoing email:\n{}",
+                message.body
+            );
+            Ok(())
+        })
+    }
+}
+
+// Appends emails to a local file, meant for local testing
+pub struct FileMailer {
+    path: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileMailer { path: path.into() }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send<'a>(&'a self, message: &'a EmailMessage) -> MailerFuture<'a> {
+        Box::pin(async move {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await
+                .map_err(MailerError::Io)?;
+
+            let entry = format!(
+                "Date: {}\nTo: {}\nSubject: {}\n\n{}\n\n----\n\n",
+                Utc::now().to_rfc2822(),
+                message.to,
+                message.subject,
+                message.body
+            );
+
+            file.write_all(entry.as_bytes()).await.map_err(MailerError::Io)
+        })
+    }
+}
+
+pub fn mailer_from_config(app_config: &Config) -> Arc<dyn Mailer> {
+    match app_config.mailer() {
+        "file" => Arc::new(FileMailer::new(app_config.mailer_file_path())),
+        _ => Arc::new(LogMailer),
+    }
+}
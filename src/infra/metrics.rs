@@ -0,0 +1,47 @@
+use std::sync::LazyLock;
+
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_gauge, Encoder, HistogramVec, IntCounter, IntGauge,
+    TextEncoder,
+};
+
+// Password hashing jobs waiting for a free worker
+pub static PASSWORD_HASH_QUEUE_DEPTH: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "password_hash_queue_depth",
+        "Password hashing jobs waiting for a worker"
+    )
+    .expect("password_hash_queue_depth is registered once")
+});
+
+// Jobs refused because the queue was full
+pub static PASSWORD_HASH_REJECTED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "password_hash_rejected_total",
+        "Password hashing jobs rejected because the queue was full"
+    )
+    .expect("password_hash_rejected_total is registered once")
+});
+
+// Time spent computing hashes, by operation (`hash` or `verify`)
+pub static PASSWORD_HASH_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "password_hash_duration_seconds",
+        "Time spent hashing or verifying a password",
+        &["operation"],
+        vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]
+    )
+    .expect("password_hash_duration_seconds is registered once")
+});
+
+// Current value of every registered metric, in the Prometheus text format
+pub fn render() -> Result<String, prometheus::Error> {
+    // Metrics are registered on first use, forcing them keeps the output stable from the start
+    LazyLock::force(&PASSWORD_HASH_QUEUE_DEPTH);
+    LazyLock::force(&PASSWORD_HASH_REJECTED);
+    LazyLock::force(&PASSWORD_HASH_DURATION);
+
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
@@ -1,3 +1,7 @@
+pub mod blocking_pool;
+pub mod breached_passwords;
 pub mod db;
 pub mod errors;
+pub mod mailer;
+pub mod metrics;
 pub mod repositories;
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::api_key::ApiKeyModel;
+use crate::infra::db::schema::api_keys;
+use crate::infra::errors::{adapt_infra_error, InfraError};
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = api_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiKeyDb {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = api_keys)]
+pub struct NewApiKeyDb {
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub async fn insert(
+    pool: &deadpool_diesel::postgres::Pool,
+    new_api_key: NewApiKeyDb,
+) -> Result<ApiKeyModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(|conn| {
+            diesel::insert_into(api_keys::table)
+                .values(new_api_key)
+                .returning(ApiKeyDb::as_returning())
+                .get_result(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(adapt_api_key_db_to_api_key(res))
+}
+
+pub async fn get(
+    pool: &deadpool_diesel::postgres::Pool,
+    id: Uuid,
+) -> Result<ApiKeyModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            api_keys::table
+                .filter(api_keys::id.eq(id))
+                .select(ApiKeyDb::as_select())
+                .get_result(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(adapt_api_key_db_to_api_key(res))
+}
+
+pub async fn find_by_hash(
+    pool: &deadpool_diesel::postgres::Pool,
+    key_hash: String,
+) -> Result<Option<ApiKeyModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            api_keys::table
+                .filter(api_keys::key_hash.eq(key_hash))
+                .select(ApiKeyDb::as_select())
+                .first::<ApiKeyDb>(conn)
+                .optional()
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res.map(adapt_api_key_db_to_api_key))
+}
+
+pub async fn get_all_for_user(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<Vec<ApiKeyModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            api_keys::table
+                .filter(api_keys::user_id.eq(user_id))
+                .order(api_keys::created_at.desc())
+                .select(ApiKeyDb::as_select())
+                .load::<ApiKeyDb>(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res.into_iter().map(adapt_api_key_db_to_api_key).collect())
+}
+
+pub async fn revoke(
+    pool: &deadpool_diesel::postgres::Pool,
+    id: Uuid,
+) -> Result<ApiKeyModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(api_keys::table.filter(api_keys::id.eq(id)))
+                .set(api_keys::revoked_at.eq(Utc::now()))
+                .returning(ApiKeyDb::as_returning())
+                .get_result::<ApiKeyDb>(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(adapt_api_key_db_to_api_key(res))
+}
+
+// Records when and from where the key was last used
+pub async fn touch(
+    pool: &deadpool_diesel::postgres::Pool,
+    id: Uuid,
+    ip_address: Option<String>,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(move |conn| {
+        diesel::update(api_keys::table.filter(api_keys::id.eq(id)))
+            .set((
+                api_keys::last_used_at.eq(Utc::now()),
+                api_keys::last_used_ip.eq(ip_address),
+            ))
+            .execute(conn)
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+fn adapt_api_key_db_to_api_key(api_key_db: ApiKeyDb) -> ApiKeyModel {
+    ApiKeyModel {
+        id: api_key_db.id,
+        user_id: api_key_db.user_id,
+        name: api_key_db.name,
+        prefix: api_key_db.prefix,
+        key_hash: api_key_db.key_hash,
+        scopes: api_key_db.scopes,
+        created_at: api_key_db.created_at,
+        expires_at: api_key_db.expires_at,
+        last_used_at: api_key_db.last_used_at,
+        last_used_ip: api_key_db.last_used_ip,
+        revoked_at: api_key_db.revoked_at,
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use diesel::{Connection, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::infra::db::schema::{email_verification_tokens, users};
+use crate::infra::errors::{adapt_infra_error, InfraError};
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = email_verification_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EmailVerificationTokenDb {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub email: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = email_verification_tokens)]
+pub struct NewEmailVerificationTokenDb {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub email: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn insert(
+    pool: &deadpool_diesel::postgres::Pool,
+    new_token: NewEmailVerificationTokenDb,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(|conn| {
+        diesel::insert_into(email_verification_tokens::table)
+            .values(new_token)
+            .execute(conn)
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+// Returns when the most recent verification email was issued to the user, used for throttling
+pub async fn latest_created_at(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<Option<DateTime<Utc>>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            email_verification_tokens::table
+                .filter(email_verification_tokens::user_id.eq(user_id))
+                .select(diesel::dsl::max(email_verification_tokens::created_at))
+                .first::<Option<DateTime<Utc>>>(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+// Marks the token as used and the user's email as verified, returning the user id.
+// Tokens issued for an address the user no longer has are rejected.
+pub async fn consume(
+    pool: &deadpool_diesel::postgres::Pool,
+    token_hash: String,
+) -> Result<Option<Uuid>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let now = Utc::now();
+                let token = email_verification_tokens::table
+                    .filter(email_verification_tokens::token_hash.eq(token_hash))
+                    .filter(email_verification_tokens::used_at.is_null())
+                    .filter(email_verification_tokens::expires_at.gt(now))
+                    .select(EmailVerificationTokenDb::as_select())
+                    .for_update()
+                    .first::<EmailVerificationTokenDb>(conn)
+                    .optional()?;
+
+                let Some(token) = token else {
+                    return Ok(None);
+                };
+
+                diesel::update(email_verification_tokens::table.filter(email_verification_tokens::id.eq(token.id)))
+                    .set(email_verification_tokens::used_at.eq(now))
+                    .execute(conn)?;
+
+                let verified = diesel::update(
+                    users::table
+                        .filter(users::id.eq(token.user_id))
+                        .filter(users::email.eq(token.email)),
+                )
+                .set(users::email_verified_at.eq(now))
+                .execute(conn)?;
+
+                Ok::<_, diesel::result::Error>((verified > 0).then_some(token.user_id))
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
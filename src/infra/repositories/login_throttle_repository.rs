@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, Utc};
+use diesel::dsl::case_when;
+use diesel::sql_types::{Nullable, Timestamptz};
+use diesel::{BoolExpressionMethods, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::login_throttle::LoginThrottleModel;
+use crate::infra::db::schema::login_throttles;
+use crate::infra::errors::{adapt_infra_error, InfraError};
+
+#[derive(Serialize, Deserialize, Queryable, Selectable, Insertable)]
+#[diesel(table_name = login_throttles)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LoginThrottleDb {
+    pub scope: String,
+    pub subject: String,
+    pub failed_attempts: i32,
+    pub last_failed_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+pub async fn get(
+    pool: &deadpool_diesel::postgres::Pool,
+    scope: &'static str,
+    subject: String,
+) -> Result<Option<LoginThrottleModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            login_throttles::table
+                .filter(login_throttles::scope.eq(scope))
+                .filter(login_throttles::subject.eq(subject))
+                .select(LoginThrottleDb::as_select())
+                .first::<LoginThrottleDb>(conn)
+                .optional()
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res.map(adapt_login_throttle_db_to_login_throttle))
+}
+
+// Counts one more failure and locks the subject once `max_attempts` is reached.
+// Counting restarts when the previous failure is older than `window` or a lockout has expired.
+// The count is incremented by the upsert itself, concurrent failures all add up even on a new row.
+pub async fn record_failure(
+    pool: &deadpool_diesel::postgres::Pool,
+    scope: &'static str,
+    subject: String,
+    window: Duration,
+    max_attempts: i32,
+    lockout: Duration,
+) -> Result<LoginThrottleModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            let now = Utc::now();
+            let first_failure = LoginThrottleDb {
+                scope: scope.to_string(),
+                subject,
+                failed_attempts: 1,
+                last_failed_at: now,
+                locked_until: (max_attempts <= 1).then(|| now + lockout),
+            };
+
+            let still_counting = login_throttles::last_failed_at.gt(now - window).and(
+                login_throttles::locked_until
+                    .is_null()
+                    .or(login_throttles::locked_until.gt(now)),
+            );
+            let failed_attempts = case_when(still_counting, login_throttles::failed_attempts + 1).otherwise(1);
+
+            diesel::insert_into(login_throttles::table)
+                .values(&first_failure)
+                .on_conflict((login_throttles::scope, login_throttles::subject))
+                .do_update()
+                .set((
+                    login_throttles::failed_attempts.eq(failed_attempts),
+                    login_throttles::last_failed_at.eq(now),
+                    login_throttles::locked_until.eq(case_when::<_, _, Nullable<Timestamptz>>(
+                        failed_attempts.ge(max_attempts),
+                        Some(now + lockout),
+                    )),
+                ))
+                .returning(LoginThrottleDb::as_returning())
+                .get_result::<LoginThrottleDb>(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(adapt_login_throttle_db_to_login_throttle(res))
+}
+
+pub async fn clear(
+    pool: &deadpool_diesel::postgres::Pool,
+    scope: &'static str,
+    subject: String,
+) -> Result<usize, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            diesel::delete(
+                login_throttles::table
+                    .filter(login_throttles::scope.eq(scope))
+                    .filter(login_throttles::subject.eq(subject)),
+            )
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+fn adapt_login_throttle_db_to_login_throttle(throttle_db: LoginThrottleDb) -> LoginThrottleModel {
+    LoginThrottleModel {
+        scope: throttle_db.scope,
+        subject: throttle_db.subject,
+        failed_attempts: throttle_db.failed_attempts,
+        last_failed_at: throttle_db.last_failed_at,
+        locked_until: throttle_db.locked_until,
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, Utc};
+use diesel::{BoolExpressionMethods, Connection, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::mfa::{MfaChallengeModel, TotpModel};
+use crate::infra::db::schema::{mfa_challenges, mfa_recovery_codes, users};
+use crate::infra::errors::{adapt_infra_error, InfraError};
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TotpDb {
+    pub id: Uuid,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
+    pub totp_last_step: Option<i64>,
+}
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = mfa_challenges)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MfaChallengeDb {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub challenge_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub consumed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = mfa_challenges)]
+pub struct NewMfaChallengeDb {
+    pub user_id: Uuid,
+    pub challenge_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn get_totp(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<TotpModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            users::table
+                .filter(users::id.eq(user_id))
+                .select(TotpDb::as_select())
+                .get_result(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(adapt_totp_db_to_totp(res))
+}
+
+// Stores a new secret awaiting confirmation, replacing any unconfirmed one
+pub async fn set_pending_totp_secret(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    encrypted_secret: String,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(move |conn| {
+        diesel::update(
+            users::table
+                .filter(users::id.eq(user_id))
+                .filter(users::totp_enabled_at.is_null()),
+        )
+        .set((
+            users::totp_secret.eq(Some(encrypted_secret)),
+            users::totp_last_step.eq(None::<i64>),
+        ))
+        .execute(conn)
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+// Activates the pending secret and replaces the recovery codes, returns false when
+// TOTP was already enabled in the meantime
+pub async fn enable_totp(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    used_step: i64,
+    recovery_code_hashes: Vec<String>,
+) -> Result<bool, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let enabled = diesel::update(
+                    users::table
+                        .filter(users::id.eq(user_id))
+                        .filter(users::totp_enabled_at.is_null())
+                        .filter(users::totp_secret.is_not_null()),
+                )
+                .set((
+                    users::totp_enabled_at.eq(Some(Utc::now())),
+                    users::totp_last_step.eq(Some(used_step)),
+                ))
+                .execute(conn)?;
+
+                if enabled == 0 {
+                    return Ok(false);
+                }
+
+                diesel::delete(mfa_recovery_codes::table.filter(mfa_recovery_codes::user_id.eq(user_id)))
+                    .execute(conn)?;
+
+                let rows: Vec<_> = recovery_code_hashes
+                    .into_iter()
+                    .map(|code_hash| {
+                        (
+                            mfa_recovery_codes::user_id.eq(user_id),
+                            mfa_recovery_codes::code_hash.eq(code_hash),
+                        )
+                    })
+                    .collect();
+
+                diesel::insert_into(mfa_recovery_codes::table)
+                    .values(rows)
+                    .execute(conn)?;
+
+                Ok::<_, diesel::result::Error>(true)
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+pub async fn disable_totp(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(move |conn| {
+        conn.transaction(|conn| {
+            diesel::update(users::table.filter(users::id.eq(user_id)))
+                .set((
+                    users::totp_secret.eq(None::<String>),
+                    users::totp_enabled_at.eq(None::<DateTime<Utc>>),
+                    users::totp_last_step.eq(None::<i64>),
+                ))
+                .execute(conn)?;
+
+            diesel::delete(mfa_recovery_codes::table.filter(mfa_recovery_codes::user_id.eq(user_id)))
+                .execute(conn)
+        })
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+// Records the time step of an accepted code, returns false when that step or a
+// later one was already used so that a code cannot be replayed
+pub async fn use_totp_step(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    step: i64,
+) -> Result<bool, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(
+                users::table
+                    .filter(users::id.eq(user_id))
+                    .filter(users::totp_last_step.is_null().or(users::totp_last_step.lt(step))),
+            )
+            .set(users::totp_last_step.eq(Some(step)))
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res > 0)
+}
+
+// Marks a recovery code as used, returns false when it is unknown or already used
+pub async fn consume_recovery_code(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    code_hash: String,
+) -> Result<bool, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(
+                mfa_recovery_codes::table
+                    .filter(mfa_recovery_codes::user_id.eq(user_id))
+                    .filter(mfa_recovery_codes::code_hash.eq(code_hash))
+                    .filter(mfa_recovery_codes::used_at.is_null()),
+            )
+            .set(mfa_recovery_codes::used_at.eq(Some(Utc::now())))
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res > 0)
+}
+
+pub async fn insert_challenge(
+    pool: &deadpool_diesel::postgres::Pool,
+    new_challenge: NewMfaChallengeDb,
+) -> Result<MfaChallengeModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(|conn| {
+            diesel::insert_into(mfa_challenges::table)
+                .values(new_challenge)
+                .returning(MfaChallengeDb::as_returning())
+                .get_result(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(adapt_mfa_challenge_db_to_mfa_challenge(res))
+}
+
+// Counts an attempt against a pending challenge before its code is checked, and returns
+// the challenge, or None when it is unknown, expired, completed or out of attempts.
+// Taking the attempt in a single UPDATE keeps concurrent guesses within the limit.
+pub async fn reserve_attempt(
+    pool: &deadpool_diesel::postgres::Pool,
+    challenge_hash: String,
+    max_attempts: i32,
+) -> Result<Option<MfaChallengeModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(
+                mfa_challenges::table
+                    .filter(mfa_challenges::challenge_hash.eq(challenge_hash))
+                    .filter(mfa_challenges::consumed_at.is_null())
+                    .filter(mfa_challenges::expires_at.gt(Utc::now()))
+                    .filter(mfa_challenges::attempts.lt(max_attempts)),
+            )
+            .set(mfa_challenges::attempts.eq(mfa_challenges::attempts + 1))
+            .returning(MfaChallengeDb::as_returning())
+            .get_result::<MfaChallengeDb>(conn)
+            .optional()
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res.map(adapt_mfa_challenge_db_to_mfa_challenge))
+}
+
+// Returns false when the challenge was completed concurrently
+pub async fn consume_challenge(
+    pool: &deadpool_diesel::postgres::Pool,
+    id: Uuid,
+) -> Result<bool, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(
+                mfa_challenges::table
+                    .filter(mfa_challenges::id.eq(id))
+                    .filter(mfa_challenges::consumed_at.is_null()),
+            )
+            .set(mfa_challenges::consumed_at.eq(Some(Utc::now())))
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res > 0)
+}
+
+fn adapt_totp_db_to_totp(totp_db: TotpDb) -> TotpModel {
+    TotpModel {
+        user_id: totp_db.id,
+        totp_secret: totp_db.totp_secret,
+        totp_enabled_at: totp_db.totp_enabled_at,
+        totp_last_step: totp_db.totp_last_step,
+    }
+}
+
+fn adapt_mfa_challenge_db_to_mfa_challenge(challenge_db: MfaChallengeDb) -> MfaChallengeModel {
+    MfaChallengeModel {
+        id: challenge_db.id,
+        user_id: challenge_db.user_id,
+        created_at: challenge_db.created_at,
+        expires_at: challenge_db.expires_at,
+        attempts: challenge_db.attempts,
+        consumed_at: challenge_db.consumed_at,
+    }
+}
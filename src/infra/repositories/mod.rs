@@ -1,2 +1,31 @@
+// Orders a boxed query by `$column` then `$id` and, given the `(value, id)` of the last
+// row of the previous page, only keeps the rows that come after it (keyset pagination)
+macro_rules! keyset_seek {
+    ($query:expr, $column:expr, $id:expr, $direction:expr, $after:expr) => {
+        match $direction {
+            $crate::domain::models::pagination::SortDirection::Asc => {
+                let query = $query.order(($column.asc(), $id.asc()));
+                match $after {
+                    Some((value, id)) => query.filter($column.gt(value.clone()).or($column.eq(value).and($id.gt(id)))),
+                    None => query,
+                }
+            }
+            $crate::domain::models::pagination::SortDirection::Desc => {
+                let query = $query.order(($column.desc(), $id.desc()));
+                match $after {
+                    Some((value, id)) => query.filter($column.lt(value.clone()).or($column.eq(value).and($id.lt(id)))),
+                    None => query,
+                }
+            }
+        }
+    };
+}
+
 pub mod user_repository;
 pub mod token_repository;
+pub mod role_repository;
+pub mod api_key_repository;
+pub mod password_reset_repository;
+pub mod email_verification_repository;
+pub mod mfa_repository;
+pub mod login_throttle_repository;
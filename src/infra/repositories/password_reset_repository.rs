@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use diesel::{Connection, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::infra::db::schema::{api_keys, password_reset_tokens, tokens, users};
+use crate::infra::errors::{adapt_infra_error, InfraError};
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = password_reset_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PasswordResetTokenDb {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub ip_address: String,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = password_reset_tokens)]
+pub struct NewPasswordResetTokenDb {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub ip_address: String,
+}
+
+pub async fn insert(
+    pool: &deadpool_diesel::postgres::Pool,
+    new_token: NewPasswordResetTokenDb,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(|conn| {
+        diesel::insert_into(password_reset_tokens::table)
+            .values(new_token)
+            .execute(conn)
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+// Returns the user of a token that can still be used, without using it
+pub async fn find_valid(
+    pool: &deadpool_diesel::postgres::Pool,
+    token_hash: String,
+) -> Result<Option<Uuid>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            password_reset_tokens::table
+                .filter(password_reset_tokens::token_hash.eq(token_hash))
+                .filter(password_reset_tokens::used_at.is_null())
+                .filter(password_reset_tokens::expires_at.gt(Utc::now()))
+                .select(password_reset_tokens::user_id)
+                .first::<Uuid>(conn)
+                .optional()
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+// Uses the token to set the user's new password and returns the user, or None when the
+// token is unknown, expired or already used. In the same transaction every other pending
+// reset token of the user is invalidated and their sessions and API keys are revoked.
+pub async fn reset_password(
+    pool: &deadpool_diesel::postgres::Pool,
+    token_hash: String,
+    password_hash: String,
+) -> Result<Option<Uuid>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let now = Utc::now();
+                let token = password_reset_tokens::table
+                    .filter(password_reset_tokens::token_hash.eq(token_hash))
+                    .filter(password_reset_tokens::used_at.is_null())
+                    .filter(password_reset_tokens::expires_at.gt(now))
+                    .select(PasswordResetTokenDb::as_select())
+                    .for_update()
+                    .first::<PasswordResetTokenDb>(conn)
+                    .optional()?;
+
+                let Some(token) = token else {
+                    return Ok(None);
+                };
+
+                diesel::update(
+                    password_reset_tokens::table
+                        .filter(password_reset_tokens::user_id.eq(token.user_id))
+                        .filter(password_reset_tokens::used_at.is_null()),
+                )
+                .set(password_reset_tokens::used_at.eq(now))
+                .execute(conn)?;
+
+                diesel::update(users::table.filter(users::id.eq(token.user_id)))
+                    .set(users::password_hash.eq(password_hash))
+                    .execute(conn)?;
+
+                // Whoever knew the old password must not keep any access
+                diesel::update(
+                    tokens::table
+                        .filter(tokens::user_id.eq(token.user_id))
+                        .filter(tokens::revoked_at.is_null()),
+                )
+                .set(tokens::revoked_at.eq(now))
+                .execute(conn)?;
+
+                diesel::update(
+                    api_keys::table
+                        .filter(api_keys::user_id.eq(token.user_id))
+                        .filter(api_keys::revoked_at.is_null()),
+                )
+                .set(api_keys::revoked_at.eq(now))
+                .execute(conn)?;
+
+                Ok::<_, diesel::result::Error>(Some(token.user_id))
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
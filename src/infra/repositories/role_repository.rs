@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::{Connection, ExpressionMethods, Insertable, JoinOnDsl, PgConnection, QueryDsl, QueryResult, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::role::{PermissionModel, RoleModel};
+use crate::infra::db::schema::{permissions, role_permissions, roles, user_roles, users};
+use crate::infra::errors::{adapt_infra_error, InfraError};
+
+// Role granting administrator rights, mirrored by `users.is_admin`
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = roles)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoleDb {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Insertable)]
+#[diesel(table_name = roles)]
+pub struct NewRoleDb {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Queryable, Selectable)]
+#[diesel(table_name = permissions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PermissionDb {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+}
+
+pub async fn insert(
+    pool: &deadpool_diesel::postgres::Pool,
+    new_role: NewRoleDb,
+    permission_ids: Vec<Uuid>,
+) -> Result<RoleModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let role = diesel::insert_into(roles::table)
+                    .values(new_role)
+                    .returning(RoleDb::as_returning())
+                    .get_result::<RoleDb>(conn)?;
+
+                replace_role_permissions(conn, role.id, &permission_ids)?;
+                load_roles_with_permissions(conn, vec![role])
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    res.into_iter().next().ok_or(InfraError::NotFound)
+}
+
+pub async fn get(
+    pool: &deadpool_diesel::postgres::Pool,
+    id: Uuid,
+) -> Result<RoleModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            let role = roles::table
+                .filter(roles::id.eq(id))
+                .select(RoleDb::as_select())
+                .get_result::<RoleDb>(conn)?;
+
+            load_roles_with_permissions(conn, vec![role])
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    res.into_iter().next().ok_or(InfraError::NotFound)
+}
+
+pub async fn get_all(
+    pool: &deadpool_diesel::postgres::Pool,
+) -> Result<Vec<RoleModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(|conn| {
+            let roles = roles::table
+                .order(roles::name.asc())
+                .select(RoleDb::as_select())
+                .load::<RoleDb>(conn)?;
+
+            load_roles_with_permissions(conn, roles)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+pub async fn delete(
+    pool: &deadpool_diesel::postgres::Pool,
+    id: Uuid,
+) -> Result<usize, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            // Administrator rights hang on the admin role, it is never deleted
+            diesel::delete(
+                roles::table
+                    .filter(roles::id.eq(id))
+                    .filter(roles::name.ne(ADMIN_ROLE)),
+            )
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+pub async fn set_permissions(
+    pool: &deadpool_diesel::postgres::Pool,
+    role_id: Uuid,
+    permission_ids: Vec<Uuid>,
+) -> Result<RoleModel, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let role = roles::table
+                    .filter(roles::id.eq(role_id))
+                    .select(RoleDb::as_select())
+                    .for_update()
+                    .get_result::<RoleDb>(conn)?;
+
+                replace_role_permissions(conn, role.id, &permission_ids)?;
+                load_roles_with_permissions(conn, vec![role])
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    res.into_iter().next().ok_or(InfraError::NotFound)
+}
+
+pub async fn get_all_permissions(
+    pool: &deadpool_diesel::postgres::Pool,
+) -> Result<Vec<PermissionModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(|conn| {
+            permissions::table
+                .order(permissions::name.asc())
+                .select(PermissionDb::as_select())
+                .load::<PermissionDb>(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res.into_iter().map(adapt_permission_db_to_permission).collect())
+}
+
+pub async fn get_user_roles(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<Vec<RoleModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            let roles = user_roles::table
+                .inner_join(roles::table)
+                .filter(user_roles::user_id.eq(user_id))
+                .order(roles::name.asc())
+                .select(RoleDb::as_select())
+                .load::<RoleDb>(conn)?;
+
+            load_roles_with_permissions(conn, roles)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+// Returns the names of every permission granted to the user through their roles
+pub async fn get_user_permissions(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<Vec<String>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            user_roles::table
+                .inner_join(role_permissions::table.on(role_permissions::role_id.eq(user_roles::role_id)))
+                .inner_join(permissions::table.on(permissions::id.eq(role_permissions::permission_id)))
+                .filter(user_roles::user_id.eq(user_id))
+                .select(permissions::name)
+                .distinct()
+                .load::<String>(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+pub async fn assign_to_user(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    role_id: Uuid,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(move |conn| {
+        conn.transaction(|conn| {
+            diesel::insert_into(user_roles::table)
+                .values((user_roles::user_id.eq(user_id), user_roles::role_id.eq(role_id)))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            sync_admin_flag(conn, user_id)
+        })
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+pub async fn remove_from_user(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    role_id: Uuid,
+) -> Result<usize, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let removed = diesel::delete(
+                    user_roles::table
+                        .filter(user_roles::user_id.eq(user_id))
+                        .filter(user_roles::role_id.eq(role_id)),
+                )
+                .execute(conn)?;
+                sync_admin_flag(conn, user_id)?;
+                Ok::<_, diesel::result::Error>(removed)
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+// Grants or revokes the admin role, keeping the user's flag in step
+pub async fn set_admin(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    is_admin: bool,
+) -> Result<(), InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    conn.interact(move |conn| {
+        conn.transaction(|conn| {
+            let admin_role_id = roles::table
+                .filter(roles::name.eq(ADMIN_ROLE))
+                .select(roles::id)
+                .get_result::<Uuid>(conn)?;
+
+            if is_admin {
+                diesel::insert_into(user_roles::table)
+                    .values((user_roles::user_id.eq(user_id), user_roles::role_id.eq(admin_role_id)))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            } else {
+                diesel::delete(
+                    user_roles::table
+                        .filter(user_roles::user_id.eq(user_id))
+                        .filter(user_roles::role_id.eq(admin_role_id)),
+                )
+                .execute(conn)?;
+            }
+            sync_admin_flag(conn, user_id)
+        })
+    })
+    .await
+    .map_err(adapt_infra_error)?
+    .map_err(adapt_infra_error)?;
+
+    Ok(())
+}
+
+// The admin role is the source of truth, `users.is_admin` only mirrors it
+fn sync_admin_flag(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<()> {
+    let is_admin = diesel::select(diesel::dsl::exists(
+        user_roles::table
+            .inner_join(roles::table)
+            .filter(user_roles::user_id.eq(user_id))
+            .filter(roles::name.eq(ADMIN_ROLE)),
+    ))
+    .get_result::<bool>(conn)?;
+
+    diesel::update(users::table.filter(users::id.eq(user_id)))
+        .set(users::is_admin.eq(is_admin))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn replace_role_permissions(
+    conn: &mut PgConnection,
+    role_id: Uuid,
+    permission_ids: &[Uuid],
+) -> QueryResult<()> {
+    diesel::delete(role_permissions::table.filter(role_permissions::role_id.eq(role_id)))
+        .execute(conn)?;
+
+    let rows: Vec<_> = permission_ids
+        .iter()
+        .map(|permission_id| {
+            (
+                role_permissions::role_id.eq(role_id),
+                role_permissions::permission_id.eq(*permission_id),
+            )
+        })
+        .collect();
+
+    diesel::insert_into(role_permissions::table)
+        .values(rows)
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
+
+// Loads the permission names of the given roles in a single query
+fn load_roles_with_permissions(
+    conn: &mut PgConnection,
+    roles: Vec<RoleDb>,
+) -> QueryResult<Vec<RoleModel>> {
+    let role_ids: Vec<Uuid> = roles.iter().map(|role| role.id).collect();
+
+    let pairs = role_permissions::table
+        .inner_join(permissions::table)
+        .filter(role_permissions::role_id.eq_any(role_ids))
+        .order(permissions::name.asc())
+        .select((role_permissions::role_id, permissions::name))
+        .load::<(Uuid, String)>(conn)?;
+
+    let mut permissions_by_role: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (role_id, permission) in pairs {
+        permissions_by_role.entry(role_id).or_default().push(permission);
+    }
+
+    Ok(roles
+        .into_iter()
+        .map(|role_db| {
+            let permissions = permissions_by_role.remove(&role_db.id).unwrap_or_default();
+            adapt_role_db_to_role(role_db, permissions)
+        })
+        .collect())
+}
+
+fn adapt_role_db_to_role(role_db: RoleDb, permissions: Vec<String>) -> RoleModel {
+    RoleModel {
+        id: role_db.id,
+        name: role_db.name,
+        description: role_db.description,
+        created_at: role_db.created_at,
+        permissions,
+    }
+}
+
+fn adapt_permission_db_to_permission(permission_db: PermissionDb) -> PermissionModel {
+    PermissionModel {
+        id: permission_db.id,
+        name: permission_db.name,
+        description: permission_db.description,
+    }
+}
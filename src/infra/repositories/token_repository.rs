@@ -1,8 +1,11 @@
 use chrono::{DateTime, Utc};
-use diesel::{AsChangeset, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use diesel::pg::Pg;
+use diesel::{AsChangeset, BoolExpressionMethods, Connection, PgConnection, QueryResult, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use crate::domain::models::token::{TokenError, TokenModel};
+use validator::{Validate, ValidationError};
+use crate::domain::models::pagination::{validate_cursor, Cursor, Page, SortDirection, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use crate::domain::models::token::TokenModel;
 use crate::infra::db::schema::{tokens};
 use crate::infra::errors::{adapt_infra_error, InfraError};
 #[derive(Serialize, Queryable, Selectable)]
@@ -24,6 +27,18 @@ pub struct TokenDb {
 #[derive(Deserialize, Insertable)]
 #[diesel(table_name = tokens)]
 pub struct NewTokenDb {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub previous_token_id: Option<Uuid>,
+}
+
+
+// Replacement minted by `rotate`, its owner and predecessor come from the presented token
+pub struct ReplacementTokenDb {
     pub token_hash: String,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
@@ -31,23 +46,129 @@ pub struct NewTokenDb {
     pub user_agent: String,
 }
 
+#[derive(Debug)]
+pub enum RotationOutcome {
+    Rotated(TokenModel),
+    // Unknown, expired or revoked token
+    Invalid,
+    // The token had already been rotated, its whole family is now revoked
+    Reused,
+}
+
 
 #[derive(AsChangeset, Deserialize)]
 #[diesel(table_name = tokens)]
 pub struct UpdateTokenDb {
-    pub expires_at: DateTime<Utc>,
-    pub revoked_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
     pub replaced_by: Option<Uuid>,
 }
 
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSortField {
+    #[default]
+    CreatedAt,
+    ExpiresAt,
+}
+
+impl TokenSortField {
+    fn as_str(self) -> &'static str {
+        match self {
+            TokenSortField::CreatedAt => "created_at",
+            TokenSortField::ExpiresAt => "expires_at",
+        }
+    }
+
+    // Value written in the cursor of the page ending with this token
+    fn cursor_value(self, token: &TokenModel) -> String {
+        match self {
+            TokenSortField::CreatedAt => token.created_at.to_rfc3339(),
+            TokenSortField::ExpiresAt => token.expires_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = validate_tokens_filter))]
 pub struct TokensFilter {
-    user_id: Option<Uuid>,
-    ip_address: Option<String>,
-    user_agent: Option<String>,
-    expires_at: Option<DateTime<Utc>>,
-    get_chained_tokens: Option<bool>,
+    pub user_id: Option<Uuid>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    // Only keep tokens that are still valid at this instant
+    pub expires_at: Option<DateTime<Utc>>,
+    // When false, tokens that have been rotated into a newer one are left out
+    pub get_chained_tokens: Option<bool>,
+    pub revoked: Option<bool>,
+    #[validate(range(min = 1, max = MAX_PAGE_LIMIT))]
+    pub limit: Option<i64>,
+    // `next_cursor` of the previous page
+    pub cursor: Option<Cursor>,
+    #[serde(default)]
+    pub sort: TokenSortField,
+    #[serde(default)]
+    pub direction: SortDirection,
+    #[serde(default)]
+    pub include_total: bool,
+}
+
+fn validate_tokens_filter(filter: &TokensFilter) -> Result<(), ValidationError> {
+    let Some(cursor) = &filter.cursor else {
+        return Ok(());
+    };
+    validate_token_cursor(cursor, filter.sort, filter.direction)
+}
+
+// Checks a cursor was issued for this order of tokens, every token sort field is a timestamp
+pub fn validate_token_cursor(
+    cursor: &Cursor,
+    sort: TokenSortField,
+    direction: SortDirection,
+) -> Result<(), ValidationError> {
+    validate_cursor(cursor, sort.as_str(), direction)?;
+
+    if DateTime::parse_from_rfc3339(&cursor.value).is_err() {
+        return Err(ValidationError::new("invalid_cursor"));
+    }
+    Ok(())
+}
+
+// The filtered tokens, unordered, shared by the page and the total count
+fn filtered_tokens(filter: &TokensFilter) -> tokens::BoxedQuery<'static, Pg> {
+    let mut query = tokens::table.into_boxed::<Pg>();
+
+    if let Some(user_uuid) = filter.user_id {
+        query = query.filter(tokens::user_id.eq(user_uuid));
+    }
+
+    if let Some(ip_address) = filter.ip_address.clone() {
+        if !ip_address.is_empty() {
+            query = query.filter(tokens::ip_address.eq(ip_address));
+        }
+    }
+
+    if let Some(user_agent) = filter.user_agent.clone() {
+        if !user_agent.is_empty() {
+            query = query.filter(tokens::user_agent.eq(user_agent));
+        }
+    }
+
+    if let Some(expires_at) = filter.expires_at {
+        query = query.filter(tokens::expires_at.gt(expires_at));
+    }
+
+    if filter.get_chained_tokens == Some(false) {
+        query = query.filter(tokens::replaced_by.is_null());
+    }
+
+    match filter.revoked {
+        Some(true) => query = query.filter(tokens::revoked_at.is_not_null()),
+        Some(false) => query = query.filter(tokens::revoked_at.is_null()),
+        None => {}
+    }
+
+    query
 }
 
 
@@ -89,40 +210,65 @@ pub async fn get(
     Ok(adapt_token_db_to_token(res))
 }
 
+// One page of tokens ordered by the sort field then by id, one extra row is
+// fetched to know whether another page follows
 pub async fn get_all(
     pool: &deadpool_diesel::postgres::Pool,
     filter: TokensFilter,
-) -> Result<Vec<TokenModel>, InfraError> {
-    // Parse user_id UUID early if present and non-empty
+) -> Result<Page<TokenModel>, InfraError> {
+    let limit = filter.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    let (sort, direction) = (filter.sort, filter.direction);
 
     let conn = pool.get().await.map_err(adapt_infra_error)?;
 
-    let res = conn
+    let (res, total) = conn
         .interact(move |conn| {
-            let mut query = tokens::table.into_boxed::<diesel::pg::Pg>();
+            let total = if filter.include_total {
+                Some(filtered_tokens(&filter).count().get_result::<i64>(conn)?)
+            } else {
+                None
+            };
 
-            if let Some(user_uuid) = filter.user_id {
-                query = query.filter(tokens::user_id.eq(user_uuid));
-            }
-
-            if let Some(ip_address) = filter.ip_address {
-                if !ip_address.is_empty() {
-                    query = query.filter(tokens::ip_address.eq(ip_address));
-                }
-            }
+            let query = filtered_tokens(&filter);
+            let after = filter.cursor.as_ref().and_then(|cursor| {
+                let value = DateTime::parse_from_rfc3339(&cursor.value).ok()?.with_timezone(&Utc);
+                Some((value, cursor.id))
+            });
+            let query = match sort {
+                TokenSortField::CreatedAt => keyset_seek!(query, tokens::created_at, tokens::id, direction, after),
+                TokenSortField::ExpiresAt => keyset_seek!(query, tokens::expires_at, tokens::id, direction, after),
+            };
 
-            query.select(TokenDb::as_select()).load::<TokenDb>(conn)
+            let tokens = query
+                .limit(limit + 1)
+                .select(TokenDb::as_select())
+                .load::<TokenDb>(conn)?;
+            Ok::<_, diesel::result::Error>((tokens, total))
         })
         .await
         .map_err(adapt_infra_error)?
         .map_err(adapt_infra_error)?;
 
-    let tokens: Vec<TokenModel> = res
+    let mut tokens: Vec<TokenModel> = res
         .into_iter()
-        .map(|token_db| adapt_token_db_to_token(token_db))
+        .map(adapt_token_db_to_token)
         .collect();
 
-    Ok(tokens)
+    let next_cursor = if tokens.len() as i64 > limit {
+        tokens.truncate(limit as usize);
+        tokens.last().map(|token| {
+            String::from(Cursor {
+                sort: sort.as_str().to_string(),
+                direction,
+                value: sort.cursor_value(token),
+                id: token.id,
+            })
+        })
+    } else {
+        None
+    };
+
+    Ok(Page { items: tokens, next_cursor, total })
 }
 
 
@@ -146,6 +292,145 @@ pub async fn update(
 
     Ok(adapt_token_db_to_token(res))
 }
+pub async fn revoke_all_for_user(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+) -> Result<usize, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(
+                tokens::table
+                    .filter(tokens::user_id.eq(user_id))
+                    .filter(tokens::revoked_at.is_null()),
+            )
+            .set(tokens::revoked_at.eq(Utc::now()))
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+// Revokes the presented token and mints its replacement in a single transaction.
+// The row is locked with `FOR UPDATE` so concurrent refreshes of the same token
+// are serialized and only the first one can succeed.
+pub async fn rotate(
+    pool: &deadpool_diesel::postgres::Pool,
+    token_hash: String,
+    replacement: ReplacementTokenDb,
+) -> Result<RotationOutcome, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+
+    let res = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                let current = tokens::table
+                    .filter(tokens::token_hash.eq(token_hash))
+                    .select(TokenDb::as_select())
+                    .for_update()
+                    .first::<TokenDb>(conn)
+                    .optional()?;
+
+                let current = match current {
+                    Some(current) => current,
+                    None => return Ok(RotationOutcome::Invalid),
+                };
+
+                let now = Utc::now();
+
+                // A token that has already been rotated should never be presented again,
+                // so the whole family is considered compromised
+                if current.replaced_by.is_some() {
+                    let family = find_token_family(conn, &current)?;
+                    let revoked = diesel::update(
+                        tokens::table
+                            .filter(tokens::id.eq_any(&family))
+                            .filter(tokens::revoked_at.is_null()),
+                    )
+                    .set(tokens::revoked_at.eq(now))
+                    .execute(conn)?;
+
+                    tracing::warn!(
+                        target: "security",
+                        user_id = %current.user_id,
+                        token_id = %current.id,
+                        family_size = family.len(),
+                        revoked,
+                        "Refresh token reuse detected, revoked the whole token family"
+                    );
+
+                    return Ok(RotationOutcome::Reused);
+                }
+
+                if current.revoked_at.is_some() || current.expires_at <= now {
+                    return Ok(RotationOutcome::Invalid);
+                }
+
+                let new_token = NewTokenDb {
+                    user_id: current.user_id,
+                    token_hash: replacement.token_hash,
+                    created_at: replacement.created_at,
+                    expires_at: replacement.expires_at,
+                    ip_address: replacement.ip_address,
+                    user_agent: replacement.user_agent,
+                    previous_token_id: Some(current.id),
+                };
+
+                let replacement = diesel::insert_into(tokens::table)
+                    .values(new_token)
+                    .returning(TokenDb::as_returning())
+                    .get_result::<TokenDb>(conn)?;
+
+                diesel::update(tokens::table.filter(tokens::id.eq(current.id)))
+                    .set((
+                        tokens::revoked_at.eq(now),
+                        tokens::replaced_by.eq(replacement.id),
+                    ))
+                    .execute(conn)?;
+
+                Ok::<_, diesel::result::Error>(RotationOutcome::Rotated(adapt_token_db_to_token(replacement)))
+            })
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res)
+}
+
+// Walks the rotation chain in both directions (previous_token_id backwards,
+// replaced_by forwards) and returns the ids of every token in the family
+fn find_token_family(conn: &mut PgConnection, token: &TokenDb) -> QueryResult<Vec<Uuid>> {
+    let mut family = vec![token.id];
+
+    let mut previous = token.previous_token_id;
+    while let Some(id) = previous.filter(|id| !family.contains(id)) {
+        family.push(id);
+        previous = tokens::table
+            .filter(tokens::id.eq(id))
+            .select(tokens::previous_token_id)
+            .first::<Option<Uuid>>(conn)
+            .optional()?
+            .flatten();
+    }
+
+    let mut next = token.replaced_by;
+    while let Some(id) = next.filter(|id| !family.contains(id)) {
+        family.push(id);
+        next = tokens::table
+            .filter(tokens::id.eq(id))
+            .select(tokens::replaced_by)
+            .first::<Option<Uuid>>(conn)
+            .optional()?
+            .flatten();
+    }
+
+    Ok(family)
+}
 
 
 fn adapt_token_db_to_token(token_db: TokenDb) -> TokenModel {
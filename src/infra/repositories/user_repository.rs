@@ -1,12 +1,24 @@
-use chrono::NaiveDate;
-use diesel::{AsChangeset, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::pg::Pg;
+use diesel::sql_types::Text;
+use diesel::{AsChangeset, BoolExpressionMethods, Connection, ExpressionMethods, IntoSql, PgTextExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::{Validate, ValidationError};
 
-use crate::domain::models::user::UserModel;
+use crate::domain::models::pagination::{validate_cursor, Cursor, Page, SortDirection, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use crate::domain::models::user::{UserModel, UserSearchHit};
+use crate::infra::db::functions::{greatest, lower, set_config, word_similarity, WordSimilar};
 use crate::infra::db::schema::users;
 use crate::infra::errors::{adapt_infra_error, InfraError};
 
+// Case-insensitive unique indexes, reported as the constraint of unique violations
+pub const USERNAME_UNIQUE_INDEX: &str = "users_username_lower_key";
+pub const EMAIL_UNIQUE_INDEX: &str = "users_email_lower_key";
+
+// Search results are always ordered by score, best first
+const RELEVANCE_SORT: &str = "relevance";
+
 #[derive(Serialize, Queryable, Selectable)]
 #[diesel(table_name = users)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -16,7 +28,9 @@ pub struct UserDb {
     pub username: String,
     pub password_hash: String,
     pub is_admin: bool,
-    pub created_at: NaiveDate
+    pub created_at: NaiveDate,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Insertable)]
@@ -29,20 +43,189 @@ pub struct NewUserDb {
 }
 
 
+// `is_admin` is left out, only role_repository writes it, following the admin role
 #[derive(AsChangeset, Deserialize)]
 #[diesel(table_name = users)]
+#[diesel(treat_none_as_null = true)]
 pub struct UpdateUserDb {
     pub email: String,
     pub username: String,
     pub password_hash: String,
-    pub is_admin: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
 }
 
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+    #[default]
+    CreatedAt,
+    Username,
+    Email,
+}
+
+impl UserSortField {
+    fn as_str(self) -> &'static str {
+        match self {
+            UserSortField::CreatedAt => "created_at",
+            UserSortField::Username => "username",
+            UserSortField::Email => "email",
+        }
+    }
+
+    // Value written in the cursor of the page ending with this user
+    fn cursor_value(self, user: &UserModel) -> String {
+        match self {
+            UserSortField::CreatedAt => user.created_at.to_string(),
+            UserSortField::Username => user.username.clone(),
+            UserSortField::Email => user.email.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    Prefix,
+    #[default]
+    Contains,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = validate_users_filter))]
 pub struct UsersFilter {
+    #[validate(length(max = 100))]
     usernames: Option<Vec<String>>,
+    #[validate(length(max = 254))]
     username: Option<String>,
+    // Matched case-insensitively, like the unique index
+    #[validate(length(max = 254))]
+    email: Option<String>,
+    is_admin: Option<bool>,
+    // Inclusive bounds on the creation date
+    created_from: Option<NaiveDate>,
+    created_to: Option<NaiveDate>,
+    // Looked for in usernames and emails, case-insensitively
+    #[validate(length(min = 1, max = 100))]
+    search: Option<String>,
+    #[serde(default)]
+    search_mode: SearchMode,
+    #[validate(range(min = 1, max = MAX_PAGE_LIMIT))]
+    limit: Option<i64>,
+    // `next_cursor` of the previous page
+    cursor: Option<Cursor>,
+    #[serde(default)]
+    sort: UserSortField,
+    #[serde(default)]
+    direction: SortDirection,
+    #[serde(default)]
+    include_total: bool,
+}
+
+fn validate_users_filter(filter: &UsersFilter) -> Result<(), ValidationError> {
+    if let (Some(created_from), Some(created_to)) = (filter.created_from, filter.created_to) {
+        if created_from > created_to {
+            return Err(ValidationError::new("invalid_date_range")
+                .with_message("created_from must not be after created_to".into()));
+        }
+    }
+
+    let Some(cursor) = &filter.cursor else {
+        return Ok(());
+    };
+    validate_cursor(cursor, filter.sort.as_str(), filter.direction)?;
+
+    if filter.sort == UserSortField::CreatedAt && cursor.value.parse::<NaiveDate>().is_err() {
+        return Err(ValidationError::new("invalid_cursor"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = validate_user_search_query))]
+pub struct UserSearchQuery {
+    // Compared with usernames and emails, typos and partial words still match
+    #[validate(length(min = 2, max = 100))]
+    pub q: String,
+    #[validate(range(min = 1, max = MAX_PAGE_LIMIT))]
+    limit: Option<i64>,
+    cursor: Option<Cursor>,
+    #[serde(default)]
+    include_total: bool,
+}
+
+fn validate_user_search_query(query: &UserSearchQuery) -> Result<(), ValidationError> {
+    let Some(cursor) = &query.cursor else {
+        return Ok(());
+    };
+    validate_cursor(cursor, RELEVANCE_SORT, SortDirection::Desc)?;
+
+    if cursor.value.parse::<f32>().is_err() {
+        return Err(ValidationError::new("invalid_cursor"));
+    }
+    Ok(())
+}
+
+// The filtered users, unordered, shared by the page and the total count
+fn filtered_users(filter: &UsersFilter) -> users::BoxedQuery<'static, Pg> {
+    let mut query = users::table.into_boxed::<Pg>();
+
+    if let Some(usernames) = filter.usernames.clone() {
+        if !usernames.is_empty() {
+            // Usernames are unique case-insensitively, and restricted to ASCII
+            let usernames: Vec<String> = usernames.iter().map(|username| username.to_lowercase()).collect();
+            query = query.filter(lower(users::username).eq_any(usernames));
+        }
+    }
+
+    if let Some(username) = filter.username.clone() {
+        query = query.filter(lower(users::username).eq(lower(username)))
+    }
+
+    if let Some(email) = filter.email.clone() {
+        query = query.filter(lower(users::email).eq(lower(email)));
+    }
+
+    if let Some(is_admin) = filter.is_admin {
+        query = query.filter(users::is_admin.eq(is_admin));
+    }
+
+    if let Some(created_from) = filter.created_from {
+        query = query.filter(users::created_at.ge(created_from));
+    }
+
+    if let Some(created_to) = filter.created_to {
+        query = query.filter(users::created_at.le(created_to));
+    }
+
+    if let Some(search) = filter.search.as_deref() {
+        let pattern = match filter.search_mode {
+            SearchMode::Prefix => format!("{}%", escape_like(search)),
+            SearchMode::Contains => format!("%{}%", escape_like(search)),
+        };
+        query = query.filter(users::username.ilike(pattern.clone()).or(users::email.ilike(pattern)));
+    }
+
+    query
+}
+
+// Users resembling `q` closely enough, unordered, shared by the page and the total count.
+// Only meaningful once the similarity threshold is set, see `search`.
+fn matching_users(q: &str) -> users::BoxedQuery<'static, Pg> {
+    users::table
+        .into_boxed::<Pg>()
+        .filter(
+            WordSimilar::new(q.to_string().into_sql::<Text>(), users::username)
+                .or(WordSimilar::new(q.to_string().into_sql::<Text>(), users::email)),
+        )
+}
+
+// Searched text is matched literally, `%` and `_` are not wildcards
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
 pub async fn insert(
@@ -83,6 +266,7 @@ pub async fn get(
     Ok(adapt_user_db_to_user(res))
 }
 
+// Usernames and emails are matched case-insensitively, like their unique indexes
 pub async fn find_by_username(
     pool: &deadpool_diesel::postgres::Pool,
     username: String,
@@ -92,7 +276,7 @@ pub async fn find_by_username(
     let res = conn
         .interact(move |conn| {
             users::table
-                .filter(users::username.eq(username))
+                .filter(lower(users::username).eq(lower(username)))
                 .select(UserDb::as_select())
                 .first::<UserDb>(conn)
                 .optional()
@@ -104,41 +288,162 @@ pub async fn find_by_username(
     Ok(res.map(adapt_user_db_to_user))
 }
 
+pub async fn find_by_email(
+    pool: &deadpool_diesel::postgres::Pool,
+    email: String,
+) -> Result<Option<UserModel>, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+
+    let res = conn
+        .interact(move |conn| {
+            users::table
+                .filter(lower(users::email).eq(lower(email)))
+                .select(UserDb::as_select())
+                .first::<UserDb>(conn)
+                .optional()
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res.map(adapt_user_db_to_user))
+}
+
+// One page of users ordered by the sort field then by id, one extra row is
+// fetched to know whether another page follows
 pub async fn get_all(
     pool: &deadpool_diesel::postgres::Pool,
     filter: UsersFilter,
-) -> Result<Vec<UserModel>, InfraError> {
+) -> Result<Page<UserModel>, InfraError> {
+    let limit = filter.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    let (sort, direction) = (filter.sort, filter.direction);
+
     let conn = pool.get().await.map_err(adapt_infra_error)?;
-    let res = conn
+    let (res, total) = conn
         .interact(move |conn| {
-            let mut query = users::table.into_boxed::<diesel::pg::Pg>();
+            let total = if filter.include_total {
+                Some(filtered_users(&filter).count().get_result::<i64>(conn)?)
+            } else {
+                None
+            };
 
-            if let Some(usernames) = filter.usernames {
-                if !usernames.is_empty() {
-                    print!("Querying multiple usernames");
-                    query = query.filter(users::username.eq_any(usernames));
+            let query = filtered_users(&filter);
+            let cursor = filter.cursor.as_ref();
+            let query = match sort {
+                UserSortField::CreatedAt => {
+                    let after = cursor.and_then(|cursor| Some((cursor.value.parse::<NaiveDate>().ok()?, cursor.id)));
+                    keyset_seek!(query, users::created_at, users::id, direction, after)
+                }
+                UserSortField::Username => {
+                    let after = cursor.map(|cursor| (cursor.value.clone(), cursor.id));
+                    keyset_seek!(query, users::username, users::id, direction, after)
                 }
-            }
+                UserSortField::Email => {
+                    let after = cursor.map(|cursor| (cursor.value.clone(), cursor.id));
+                    keyset_seek!(query, users::email, users::id, direction, after)
+                }
+            };
 
-            if let Some(username) = filter.username {
-                print!("Querying single username");
-                query = query.filter(users::username.eq(username))
-            }
+            let users = query
+                .limit(limit + 1)
+                .select(UserDb::as_select())
+                .load::<UserDb>(conn)?;
+            Ok::<_, diesel::result::Error>((users, total))
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
 
-            //println!("SQL Query: {}", debug_query::<Pg, _>(&query));
+    let mut users: Vec<UserModel> = res
+        .into_iter()
+        .map(adapt_user_db_to_user)
+        .collect();
 
-            query.select(UserDb::as_select()).load::<UserDb>(conn)
+    let next_cursor = if users.len() as i64 > limit {
+        users.truncate(limit as usize);
+        users.last().map(|user| {
+            String::from(Cursor {
+                sort: sort.as_str().to_string(),
+                direction,
+                value: sort.cursor_value(user),
+                id: user.id,
+            })
+        })
+    } else {
+        None
+    };
+
+    Ok(Page { items: users, next_cursor, total })
+}
+
+// One page of users whose username or email resembles the query, best matches first.
+// The score is the pg_trgm word similarity of the closest of the two fields.
+pub async fn search(
+    pool: &deadpool_diesel::postgres::Pool,
+    query: UserSearchQuery,
+    min_similarity: f32,
+) -> Result<Page<UserSearchHit>, InfraError> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+    let (res, total) = conn
+        .interact(move |conn| {
+            conn.transaction(|conn| {
+                // Threshold of the `<%` operator, local to this transaction so pooled
+                // connections keep the default
+                diesel::select(set_config(
+                    "pg_trgm.word_similarity_threshold",
+                    min_similarity.to_string(),
+                    true,
+                ))
+                .execute(conn)?;
+
+                let total = if query.include_total {
+                    Some(matching_users(&query.q).count().get_result::<i64>(conn)?)
+                } else {
+                    None
+                };
+
+                let score = greatest(
+                    word_similarity(query.q.clone(), users::username),
+                    word_similarity(query.q.clone(), users::email),
+                );
+                let after = query
+                    .cursor
+                    .as_ref()
+                    .and_then(|cursor| Some((cursor.value.parse::<f32>().ok()?, cursor.id)));
+                let hits = keyset_seek!(matching_users(&query.q), score.clone(), users::id, SortDirection::Desc, after)
+                    .limit(limit + 1)
+                    .select((UserDb::as_select(), score))
+                    .load::<(UserDb, f32)>(conn)?;
+                Ok::<_, diesel::result::Error>((hits, total))
+            })
         })
         .await
         .map_err(adapt_infra_error)?
         .map_err(adapt_infra_error)?;
 
-    let users: Vec<UserModel> = res
+    let mut hits: Vec<UserSearchHit> = res
         .into_iter()
-        .map(|user_db| adapt_user_db_to_user(user_db))
+        .map(|(user_db, score)| UserSearchHit { user: adapt_user_db_to_user(user_db), score })
         .collect();
 
-    Ok(users)
+    let next_cursor = if hits.len() as i64 > limit {
+        hits.truncate(limit as usize);
+        hits.last().map(|hit| {
+            String::from(Cursor {
+                sort: RELEVANCE_SORT.to_string(),
+                direction: SortDirection::Desc,
+                // Shortest representation that parses back to the same f32
+                value: hit.score.to_string(),
+                id: hit.user.id,
+            })
+        })
+    } else {
+        None
+    };
+
+    Ok(Page { items: hits, next_cursor, total })
 }
 
 pub async fn update(
@@ -163,6 +468,33 @@ pub async fn update(
 }
 
 
+// Replaces the password hash only if it is still `old_hash`, so a password changed in
+// the meantime is kept. Returns false when nothing was updated.
+pub async fn update_password_hash(
+    pool: &deadpool_diesel::postgres::Pool,
+    user_id: Uuid,
+    old_hash: String,
+    new_hash: String,
+) -> Result<bool, InfraError> {
+    let conn = pool.get().await.map_err(adapt_infra_error)?;
+
+    let res = conn
+        .interact(move |conn| {
+            diesel::update(
+                users::table
+                    .filter(users::id.eq(user_id))
+                    .filter(users::password_hash.eq(old_hash)),
+            )
+            .set(users::password_hash.eq(new_hash))
+            .execute(conn)
+        })
+        .await
+        .map_err(adapt_infra_error)?
+        .map_err(adapt_infra_error)?;
+
+    Ok(res > 0)
+}
+
 fn adapt_user_db_to_user(user_db: UserDb) -> UserModel {
     UserModel {
         id: user_db.id,
@@ -170,7 +502,9 @@ fn adapt_user_db_to_user(user_db: UserDb) -> UserModel {
         username: user_db.username,
         is_admin: user_db.is_admin,
         password_hash: user_db.password_hash,
-        created_at: user_db.created_at
+        created_at: user_db.created_at,
+        email_verified_at: user_db.email_verified_at,
+        totp_enabled_at: user_db.totp_enabled_at,
     }
 }
 
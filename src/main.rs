@@ -2,6 +2,8 @@ use deadpool_diesel::postgres::{Manager, Pool};
 use deadpool_diesel::Runtime;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::prelude::*;
 use diesel_migrations::MigrationHarness;
 
@@ -18,7 +20,11 @@ mod utils;
 // Import necessary items from modules
 use crate::config::config;
 use crate::errors::{internal_error, AppError};
-use crate::routes::app_router;
+use crate::infra::blocking_pool::BlockingPool;
+use crate::infra::breached_passwords::BreachedPasswords;
+use crate::infra::metrics::PASSWORD_HASH_QUEUE_DEPTH;
+use crate::infra::mailer::mailer_from_config;
+use crate::routes::{app_router, metrics_router};
 use crate::state::AppState;
 
 // Embed database migrations
@@ -35,8 +41,11 @@ async fn main() {
 
     // Create a connection manager for the database pool
     let manager = Manager::new(app_config.db_url().to_string(), Runtime::Tokio1);
-    // Build the connection pool
+    // Build the connection pool, waiting for a free connection is bounded so a saturated
+    // pool answers with a 503 instead of holding requests forever
     let pool = Pool::builder(manager)
+        .wait_timeout(Some(Duration::from_secs(app_config.db_pool_wait_timeout_seconds())))
+        .runtime(Runtime::Tokio1)
         .build()
         .expect("Failed to create connection pool");
 
@@ -47,8 +56,40 @@ async fn main() {
         return;
     }
 
-    // Create application state containing the connection pool
-    let state = AppState { pool };
+    // Load the breached password list once, lookups then stay in memory
+    let breached_passwords = match app_config.password_breached_list_path() {
+        Some(path) => {
+            let breached_passwords = BreachedPasswords::load(path)
+                .await
+                .expect("Failed to load PASSWORD_BREACHED_LIST_PATH");
+            // An unreadable list would otherwise silently disable the breach check
+            assert!(
+                !breached_passwords.is_empty(),
+                "Breached password list {} contains no SHA-1 hashes",
+                path
+            );
+            tracing::info!("Loaded {} breached password hashes", breached_passwords.len());
+            breached_passwords
+        }
+        None => BreachedPasswords::empty(),
+    };
+
+    // Password hashing runs on its own threads to keep the runtime responsive under login bursts
+    let hashing_pool = BlockingPool::new(
+        "password-hash",
+        app_config.password_hash_workers(),
+        app_config.password_hash_queue_limit(),
+        PASSWORD_HASH_QUEUE_DEPTH.clone(),
+    );
+
+    // Create application state containing the connection pool, the mailer, the breached passwords
+    // and the password hashing pool
+    let state = AppState {
+        pool,
+        mailer: mailer_from_config(app_config),
+        breached_passwords: Arc::new(breached_passwords),
+        hashing_pool: Arc::new(hashing_pool),
+    };
 
     // Create the application router with the defined routes
     let app = app_router(state.clone());
@@ -63,13 +104,27 @@ async fn main() {
     // Log the server address
     tracing::info!("listening on http://{}", socket_addr);
 
+    // Metrics are served on their own address, which is not meant to be exposed publicly
+    let metrics_address = format!("{}:{}", app_config.metrics_host(), app_config.metrics_port());
+    let metrics_socket_addr: SocketAddr = metrics_address.parse().expect("Unable to parse metrics socket address");
+    let metrics_listener = tokio::net::TcpListener::bind(metrics_socket_addr)
+        .await
+        .expect("Failed to bind metrics listener");
+    tracing::info!("serving metrics on http://{}/metrics", metrics_socket_addr);
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(metrics_listener, metrics_router()).await {
+            tracing::error!("Metrics server failed: {}", err);
+        }
+    });
+
     // Bind the server to the specified address
     let listener = tokio::net::TcpListener::bind(socket_addr)
         .await
         .expect("Failed to bind");
 
     // Start the axum server
-    axum::serve(listener, app)
+    // Serve with connection info so handlers can extract the client's `SocketAddr`
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .expect("Server failed to run");
 }
@@ -4,10 +4,32 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use axum::routing::patch;
+use axum::http::header::CONTENT_TYPE;
+use axum::middleware;
+use axum::routing::{delete, patch, put};
+
+use crate::errors::Problem;
+use crate::infra::metrics;
+use crate::utils::correlation_id::correlation_id;
 
 // Import handlers for user-related operations
-use crate::handlers::users::{create_user, get_user, list_users, patch_user, login_user};
+use crate::handlers::users::{
+    confirm_password_reset, confirm_totp, create_user, disable_totp, enroll_totp, get_user,
+    list_user_sessions, list_users, login_user, patch_user, request_password_reset,
+    resend_verification_email, revoke_user_session, revoke_user_sessions, search_users, set_user_admin,
+    unlock_user, verify_email, verify_login_mfa,
+};
+// Import handlers for API key-related operations
+use crate::handlers::api_keys::{create_api_key, list_api_keys, revoke_api_key};
+// Import handlers for role-related operations
+use crate::handlers::roles::{
+    assign_user_role, create_role, delete_role, get_role, list_permissions, list_roles,
+    list_user_roles, remove_user_role, set_role_permissions,
+};
+// Import handlers for token-related operations
+use crate::handlers::tokens::{
+    create_token, get_token, list_tokens, logout, refresh_token, revoke_token,
+};
 
 
 // Import the application state
@@ -19,21 +41,44 @@ pub fn app_router(state: AppState) -> Router {
         // Define the root route
         .route("/", get(root))
         .nest("/v1/users", users_routes(state.clone()))
+        .nest("/v1/tokens", tokens_routes(state.clone()))
+        .nest("/v1/roles", roles_routes(state.clone()))
+        .nest("/v1/permissions", permissions_routes(state.clone()))
         // Define a fallback handler for 404 errors
         .fallback(handler_404)
+        // Tag every request with a correlation id shared by its logs and error bodies
+        .layer(middleware::from_fn(correlation_id))
         // Attach the application state to the router
         .with_state(state)
 }
 
+// Function to create the router of the internal metrics listener
+pub fn metrics_router() -> Router {
+    Router::new()
+        // Expose the Prometheus metrics (GET /metrics)
+        .route("/metrics", get(metrics_handler))
+        // Define a fallback handler for 404 errors
+        .fallback(handler_404)
+}
+
 // Handler for the root route
 async fn root() -> &'static str {
     "Server is running!"
 }
 
+// Handler for the metrics route, in the Prometheus text format
+async fn metrics_handler() -> impl IntoResponse {
+    match metrics::render() {
+        Ok(body) => ([(CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response(),
+        Err(err) => Problem::internal(err).into_response(),
+    }
+}
+
 // Handler for 404 Not Found errors
 async fn handler_404() -> impl IntoResponse {
-    (
+    Problem::new(
         StatusCode::NOT_FOUND,
+        "not_found",
         "The requested resource was not found",
     )
 }
@@ -46,12 +91,97 @@ fn users_routes(state: AppState) -> Router<AppState> {
         .route("/", post(create_user))
         // Route for listing all users (GET /v1/posts)
         .route("/", get(list_users))
+        // Route for fuzzy searching users by username or email (GET /v1/users/search?q=)
+        .route("/search", get(search_users))
         // Route for getting a specific user by ID (GET /v1/posts/:id)
         .route("/{id}", get(get_user))
         // Route for patching a specific user by ID (PATCH /v1/posts/:id)
         .route("/{id}", patch(patch_user))
+        // Route for granting or revoking administrator rights (PUT /v1/users/:id/admin)
+        .route("/{id}/admin", put(set_user_admin))
+        // Route for lifting a login lockout (POST /v1/users/:id/unlock)
+        .route("/{id}/unlock", post(unlock_user))
+        // Route for listing the roles of a user (GET /v1/users/:id/roles)
+        .route("/{id}/roles", get(list_user_roles))
+        // Route for granting a role to a user (PUT /v1/users/:id/roles/:role_id)
+        .route("/{id}/roles/{role_id}", put(assign_user_role))
+        // Route for revoking a role from a user (DELETE /v1/users/:id/roles/:role_id)
+        .route("/{id}/roles/{role_id}", delete(remove_user_role))
+        // Route for creating a personal API key (POST /v1/users/:id/api-keys)
+        .route("/{id}/api-keys", post(create_api_key))
+        // Route for listing the API keys of a user (GET /v1/users/:id/api-keys)
+        .route("/{id}/api-keys", get(list_api_keys))
+        // Route for revoking an API key (DELETE /v1/users/:id/api-keys/:key_id)
+        .route("/{id}/api-keys/{key_id}", delete(revoke_api_key))
         // Route for handling login requests (POST /v1/posts/login)
         .route("/login", post(login_user))
+        // Route for completing a login with a second factor code (POST /v1/users/login/mfa)
+        .route("/login/mfa", post(verify_login_mfa))
+        // Route for starting TOTP enrollment (POST /v1/users/:id/mfa/totp)
+        .route("/{id}/mfa/totp", post(enroll_totp))
+        // Route for activating TOTP with a first code (POST /v1/users/:id/mfa/totp/confirm)
+        .route("/{id}/mfa/totp/confirm", post(confirm_totp))
+        // Route for turning TOTP off (POST /v1/users/:id/mfa/totp/disable)
+        .route("/{id}/mfa/totp/disable", post(disable_totp))
+        // Route for requesting a password reset email (POST /v1/users/password-reset)
+        .route("/password-reset", post(request_password_reset))
+        // Route for confirming an email address (GET /v1/users/verify-email?token=)
+        .route("/verify-email", get(verify_email))
+        // Route for requesting a new verification email (POST /v1/users/verify-email/resend)
+        .route("/verify-email/resend", post(resend_verification_email))
+        // Route for choosing a new password with a reset token (POST /v1/users/password-reset/confirm)
+        .route("/password-reset/confirm", post(confirm_password_reset))
+        // Route for listing the active sessions of a user (GET /v1/users/:id/sessions)
+        .route("/{id}/sessions", get(list_user_sessions))
+        // Route for revoking a single session of a user (DELETE /v1/users/:id/sessions/:session_id)
+        .route("/{id}/sessions/{session_id}", delete(revoke_user_session))
+        // Route for revoking every session of a user (POST /v1/users/:id/sessions/revoke-all)
+        .route("/{id}/sessions/revoke-all", post(revoke_user_sessions))
         // Attach the application state to the user's router
         .with_state(state)
 }
+
+// Function to define token-related routes
+fn tokens_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        // Route for creating a new token for the caller (POST /v1/tokens)
+        .route("/", post(create_token))
+        // Route for listing the caller's tokens (GET /v1/tokens)
+        .route("/", get(list_tokens))
+        // Route for getting a specific token by ID (GET /v1/tokens/:id)
+        .route("/{id}", get(get_token))
+        // Route for revoking a specific token by ID (DELETE /v1/tokens/:id)
+        .route("/{id}", delete(revoke_token))
+        // Route for rotating a refresh token (POST /v1/tokens/refresh)
+        .route("/refresh", post(refresh_token))
+        // Route for revoking the caller's current token (POST /v1/tokens/logout)
+        .route("/logout", post(logout))
+        // Attach the application state to the token's router
+        .with_state(state)
+}
+
+// Function to define role-related routes
+fn roles_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        // Route for creating a new role (POST /v1/roles)
+        .route("/", post(create_role))
+        // Route for listing all roles (GET /v1/roles)
+        .route("/", get(list_roles))
+        // Route for getting a specific role by ID (GET /v1/roles/:id)
+        .route("/{id}", get(get_role))
+        // Route for deleting a specific role by ID (DELETE /v1/roles/:id)
+        .route("/{id}", delete(delete_role))
+        // Route for replacing the permissions of a role (PUT /v1/roles/:id/permissions)
+        .route("/{id}/permissions", put(set_role_permissions))
+        // Attach the application state to the role's router
+        .with_state(state)
+}
+
+// Function to define permission-related routes
+fn permissions_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        // Route for listing all permissions (GET /v1/permissions)
+        .route("/", get(list_permissions))
+        // Attach the application state to the permission's router
+        .with_state(state)
+}
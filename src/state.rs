@@ -1,6 +1,15 @@
+use std::sync::Arc;
+
 use deadpool_diesel::postgres::Pool; // Ou le Pool approprié
 
+use crate::infra::blocking_pool::BlockingPool;
+use crate::infra::breached_passwords::BreachedPasswords;
+use crate::infra::mailer::Mailer;
+
 #[derive(Clone)]
 pub struct AppState {
     pub pool: Pool,
+    pub mailer: Arc<dyn Mailer>,
+    pub breached_passwords: Arc<BreachedPasswords>,
+    pub hashing_pool: Arc<BlockingPool>,
 }
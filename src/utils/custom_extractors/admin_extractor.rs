@@ -0,0 +1,27 @@
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use crate::domain::models::user::UserError;
+use crate::utils::AuthenticatedUser;
+use crate::AppState;
+
+// Only lets authenticated administrators through
+#[derive(Debug)]
+pub struct RequireAdmin(pub AuthenticatedUser);
+
+impl FromRequestParts<AppState> for RequireAdmin {
+    type Rejection = UserError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = AuthenticatedUser::from_request_parts(parts, state).await?;
+
+        if !auth.is_admin {
+            return Err(UserError::Forbidden);
+        }
+
+        Ok(RequireAdmin(auth))
+    }
+}
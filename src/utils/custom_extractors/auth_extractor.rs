@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum_extra::TypedHeader;
+use chrono::Utc;
+use headers::authorization::Bearer;
+use headers::Authorization;
+use uuid::Uuid;
+
+use crate::domain::models::user::UserError;
+use crate::handlers::api_keys::API_KEY_PREFIX;
+use crate::handlers::tokens::hash_token;
+use crate::infra::errors::InfraError;
+use crate::infra::repositories::{api_key_repository, role_repository, user_repository};
+use crate::utils::jwt::{looks_like_jwt, verify_access_token};
+use crate::AppState;
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+// Resolves the caller from an `X-Api-Key` header or an `Authorization: Bearer <token>` header.
+// Signed access tokens are verified statelessly, API keys are looked up in the database.
+#[derive(Debug)]
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub is_admin: bool,
+    // Id of the token row backing the current session, absent for API keys
+    pub session_id: Option<Uuid>,
+    pub api_key_id: Option<Uuid>,
+    pub permissions: HashSet<String>,
+}
+
+impl AuthenticatedUser {
+    // Administrators get their permissions from the admin role like everyone else
+    pub fn can(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    pub fn require(&self, permission: &str) -> Result<(), UserError> {
+        if self.can(permission) {
+            Ok(())
+        } else {
+            Err(UserError::Forbidden)
+        }
+    }
+
+    // Acting on one's own account from a session. API keys never count as their owner,
+    // they stay limited to their scopes even on the owner's resources.
+    pub fn is_self(&self, user_id: Uuid) -> bool {
+        self.user_id == user_id && self.api_key_id.is_none()
+    }
+
+    // Users may always act on their own resources, other users' need the permission
+    pub fn ensure_self_or(&self, user_id: Uuid, permission: &str) -> Result<(), UserError> {
+        if self.is_self(user_id) {
+            Ok(())
+        } else {
+            self.require(permission)
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for AuthenticatedUser {
+    type Rejection = UserError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(api_key) = parts.headers.get(API_KEY_HEADER) {
+            let api_key = api_key.to_str().map_err(|_| UserError::Unauthorized)?.to_string();
+            return authenticate_api_key(parts, state, &api_key).await;
+        }
+
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| UserError::Unauthorized)?;
+
+        if bearer.token().starts_with(API_KEY_PREFIX) {
+            return authenticate_api_key(parts, state, bearer.token()).await;
+        }
+
+        // Refresh tokens are only accepted by /v1/tokens/refresh, never as a credential
+        if !looks_like_jwt(bearer.token()) {
+            return Err(UserError::Unauthorized);
+        }
+
+        let claims = verify_access_token(bearer.token())
+            .await
+            .ok_or(UserError::Unauthorized)?;
+
+        Ok(AuthenticatedUser {
+            user_id: claims.sub,
+            is_admin: claims.adm,
+            session_id: Some(claims.sid),
+            api_key_id: None,
+            permissions: claims.perms.into_iter().collect(),
+        })
+    }
+}
+
+// API keys never carry administrator rights, they only hold the scopes that
+// were granted on creation and that their owner still has
+async fn authenticate_api_key(
+    parts: &Parts,
+    state: &AppState,
+    api_key: &str,
+) -> Result<AuthenticatedUser, UserError> {
+    let api_key = api_key_repository::find_by_hash(&state.pool, hash_token(api_key))
+        .await
+        .map_err(UserError::InfraError)?
+        .ok_or(UserError::Unauthorized)?;
+
+    let now = Utc::now();
+    if api_key.revoked_at.is_some() || api_key.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err(UserError::Unauthorized);
+    }
+
+    let user = user_repository::get(&state.pool, api_key.user_id)
+        .await
+        .map_err(|db_error| match db_error {
+            InfraError::NotFound => UserError::Unauthorized,
+            db_error => UserError::InfraError(db_error),
+        })?;
+
+    let user_permissions: HashSet<String> = role_repository::get_user_permissions(&state.pool, user.id)
+        .await
+        .map_err(UserError::InfraError)?
+        .into_iter()
+        .collect();
+
+    let permissions = api_key
+        .scopes
+        .into_iter()
+        .filter(|scope| user_permissions.contains(scope))
+        .collect();
+
+    // Usage tracking must not slow down or fail the request
+    let ip_address = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let pool = state.pool.clone();
+    tokio::spawn(async move {
+        if let Err(err) = api_key_repository::touch(&pool, api_key.id, ip_address).await {
+            tracing::warn!("Failed to record API key usage: {}", err);
+        }
+    });
+
+    Ok(AuthenticatedUser {
+        user_id: user.id,
+        is_admin: false,
+        session_id: None,
+        api_key_id: Some(api_key.id),
+        permissions,
+    })
+}
@@ -1,12 +1,28 @@
 use axum::extract::rejection::JsonRejection;
-use axum_macros::FromRequest;
+use axum::extract::{FromRequest, Request};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use validator::Validate;
 
 use crate::errors::AppError;
 
-#[derive(FromRequest)]
-#[from_request(via(axum::Json), rejection(AppError))]
+// Deserializes the JSON body and runs the payload's declared validation rules,
+// invalid payloads are rejected with a per-field error map before reaching the handler
 pub struct JsonExtractor<T>(pub T);
 
+impl<S, T> FromRequest<S> for JsonExtractor<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(payload) = Json::<T>::from_request(req, state).await?;
+        payload.validate().map_err(AppError::InvalidPayload)?;
+        Ok(JsonExtractor(payload))
+    }
+}
 
 impl From<JsonRejection> for AppError {
     fn from(rejection: JsonRejection) -> Self {
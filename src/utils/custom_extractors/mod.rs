@@ -1,2 +1,5 @@
+pub mod admin_extractor;
+pub mod auth_extractor;
 pub mod json_extractor;
 pub mod path_extractor;
+pub mod query_extractor;
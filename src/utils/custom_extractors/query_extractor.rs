@@ -0,0 +1,31 @@
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum_extra::extract::{Query, QueryRejection};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::errors::AppError;
+
+// Deserializes the query string and runs the declared validation rules, like `JsonExtractor`.
+// Repeated keys (`?usernames=a&usernames=b`) are collected into sequences.
+pub struct QueryExtractor<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for QueryExtractor<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<T>::from_request_parts(parts, state).await?;
+        params.validate().map_err(AppError::InvalidPayload)?;
+        Ok(QueryExtractor(params))
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BodyParsingError(rejection.to_string())
+    }
+}
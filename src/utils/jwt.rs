@@ -0,0 +1,74 @@
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::models::role::RoleModel;
+use crate::domain::models::user::UserModel;
+
+// Claims of the short-lived access token, `sid` is the id of the refresh token backing the session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessClaims {
+    pub sub: Uuid,
+    pub sid: Uuid,
+    pub adm: bool,
+    pub roles: Vec<String>,
+    pub perms: Vec<String>,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+pub async fn issue_access_token(
+    user: &UserModel,
+    roles: &[RoleModel],
+    session_id: Uuid,
+) -> Result<(String, DateTime<Utc>), jsonwebtoken::errors::Error> {
+    let app_config = config().await;
+    let (kid, secret) = app_config.jwt_active_key();
+
+    let now = Utc::now();
+    let expires_at = now + Duration::minutes(app_config.access_token_ttl_minutes());
+
+    let mut perms: Vec<String> = roles
+        .iter()
+        .flat_map(|role| role.permissions.iter().cloned())
+        .collect();
+    perms.sort();
+    perms.dedup();
+
+    let claims = AccessClaims {
+        sub: user.id,
+        sid: session_id,
+        adm: user.is_admin,
+        roles: roles.iter().map(|role| role.name.clone()).collect(),
+        perms,
+        iat: now.timestamp(),
+        exp: expires_at.timestamp(),
+    };
+
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some(kid.to_string());
+
+    let token = encode(&header, &claims, &EncodingKey::from_secret(secret.as_bytes()))?;
+    Ok((token, expires_at))
+}
+
+// Verifies signature and expiry without touching the database
+pub async fn verify_access_token(token: &str) -> Option<AccessClaims> {
+    let header = decode_header(token).ok()?;
+    let secret = config().await.jwt_key(header.kid.as_deref()?)?;
+
+    decode::<AccessClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map(|data| data.claims)
+    .ok()
+}
+
+// Opaque tokens are plain hex, access tokens are three dot-separated segments
+pub fn looks_like_jwt(token: &str) -> bool {
+    token.split('.').count() == 3
+}
@@ -1,5 +1,13 @@
+pub use custom_extractors::admin_extractor::RequireAdmin;
+pub use custom_extractors::auth_extractor::AuthenticatedUser;
 pub use custom_extractors::json_extractor::JsonExtractor;
 pub use custom_extractors::path_extractor::PathExtractor;
+pub use custom_extractors::query_extractor::QueryExtractor;
 
 mod custom_extractors;
+pub mod correlation_id;
+pub mod jwt;
+pub mod password_strength;
 
+pub mod totp;
+pub mod validation;
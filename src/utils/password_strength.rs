@@ -0,0 +1,251 @@
+// Rough guess-count estimator in the spirit of zxcvbn: a password is scored by how
+// many guesses an attacker trying common passwords and patterns first would need.
+// Dictionary words, repeats, sequences and keyboard walks cost almost nothing,
+// only the remaining characters are priced at the size of their character set.
+
+pub const MAX_SCORE: u8 = 4;
+
+// Most common passwords and password building blocks, lowercase and without leet
+// substitutions. Anything matching them is worth a handful of guesses.
+const COMMON_WORDS: &[&str] = &[
+    "password", "passwort", "motdepasse", "qwerty", "azerty", "letmein", "welcome", "admin",
+    "administrator", "login", "secret", "dragon", "monkey", "football", "baseball", "soccer",
+    "hockey", "iloveyou", "love", "master", "sunshine", "shadow", "princess", "trustno",
+    "superman", "batman", "starwars", "whatever", "freedom", "hello", "charlie", "michael",
+    "jordan", "hunter", "ranger", "buster", "thomas", "robert", "daniel", "jessica", "ashley",
+    "summer", "winter", "spring", "autumn", "flower", "cookie", "cheese", "computer",
+    "internet", "google", "samsung", "apple", "killer", "pepper", "ginger", "maggie", "tigger",
+    "mustang", "access", "matrix", "orange", "banana", "chocolate", "purple", "silver",
+    "golden", "diamond", "angel", "change", "changeme", "default", "guest", "root", "user",
+    "test", "demo", "temp", "pass", "god", "sex", "money", "lucky", "happy", "family",
+    "friend", "friends", "baby", "monday", "january", "december", "nanomon",
+];
+
+// Keyboard rows walked by "qwerty"-like passwords
+const KEYBOARD_ROWS: &[&str] = &[
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+    "azertyuiop",
+    "qsdfghjklm",
+    "wxcvbn",
+];
+
+// Guesses for a dictionary hit, on top of the position of the word in the list
+const DICTIONARY_BASE_GUESSES: f64 = 50.0;
+// Guesses for each character that continues a repeat, sequence or keyboard walk
+const PATTERN_CHARACTER_GUESSES: f64 = 2.0;
+// Minimum length of a run before it is treated as a pattern
+const MIN_PATTERN_LENGTH: usize = 3;
+// Years attackers try first, as in `summer2024`
+const YEAR_GUESSES: f64 = 200.0;
+const YEAR_LENGTH: usize = 4;
+
+// Maps the estimated guesses to a score from 0 to 4 with the zxcvbn thresholds.
+// `user_inputs` are values an attacker would try first, like the username.
+pub fn estimate_score(password: &str, user_inputs: &[&str]) -> u8 {
+    let log10_guesses = estimate_log10_guesses(password, user_inputs);
+    match log10_guesses {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => MAX_SCORE,
+    }
+}
+
+fn estimate_log10_guesses(password: &str, user_inputs: &[&str]) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+
+    let normalized: Vec<char> = chars.iter().map(|c| unleet(*c)).collect();
+    let cardinality = character_set_size(&chars) as f64;
+
+    let mut dictionary: Vec<String> = COMMON_WORDS.iter().map(|word| word.to_string()).collect();
+    dictionary.extend(
+        user_inputs
+            .iter()
+            .map(|input| input.to_lowercase())
+            .filter(|input| input.chars().count() >= MIN_PATTERN_LENGTH),
+    );
+
+    // Splits the password greedily into dictionary words, patterns and plain characters
+    let mut log10_guesses = 0.0;
+    let mut matched_patterns = 0usize;
+    let mut position = 0;
+    while position < chars.len() {
+        let dictionary_match = longest_dictionary_match(&normalized[position..], &dictionary);
+        let length = pattern_length(&chars[position..]);
+
+        // The longest match wins, `abcdefgh` is a sequence rather than `abc` followed by `defgh`
+        if let Some((rank, length)) = dictionary_match.filter(|(_, word_length)| *word_length >= length) {
+            let variations = if chars[position..position + length].iter().any(|c| c.is_uppercase()) {
+                2.0
+            } else {
+                1.0
+            };
+            log10_guesses += (DICTIONARY_BASE_GUESSES + rank as f64).log10() + f64::log10(variations);
+            matched_patterns += 1;
+            position += length;
+            continue;
+        }
+
+        if is_recent_year(&chars[position..]) {
+            log10_guesses += YEAR_GUESSES.log10();
+            matched_patterns += 1;
+            position += YEAR_LENGTH;
+            continue;
+        }
+
+        if length >= MIN_PATTERN_LENGTH {
+            // The first character still has to be guessed
+            log10_guesses += cardinality.log10() + (length - 1) as f64 * PATTERN_CHARACTER_GUESSES.log10();
+            matched_patterns += 1;
+            position += length;
+            continue;
+        }
+
+        log10_guesses += cardinality.log10();
+        position += 1;
+    }
+
+    // Attackers also have to guess how the pieces are combined
+    if matched_patterns > 1 {
+        log10_guesses += (matched_patterns as f64).log10();
+    }
+    log10_guesses
+}
+
+// Returns the rank and length of the longest word of the dictionary starting here
+fn longest_dictionary_match(chars: &[char], dictionary: &[String]) -> Option<(usize, usize)> {
+    dictionary
+        .iter()
+        .enumerate()
+        .filter_map(|(rank, word)| {
+            let length = word.chars().count();
+            let candidate: String = chars.iter().take(length).flat_map(|c| c.to_lowercase()).collect();
+            (length >= MIN_PATTERN_LENGTH && candidate == *word).then_some((rank, length))
+        })
+        .max_by_key(|(_, length)| *length)
+}
+
+// Length of the repeat, sequence (`abc`, `321`) or keyboard walk starting here
+fn pattern_length(chars: &[char]) -> usize {
+    let lowercase: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+
+    let repeat = run_length(&lowercase, |previous, current| previous == current);
+    let ascending = run_length(&lowercase, |previous, current| current as u32 == previous as u32 + 1);
+    let descending = run_length(&lowercase, |previous, current| current as u32 + 1 == previous as u32);
+    let keyboard = run_length(&lowercase, is_keyboard_neighbour);
+
+    [repeat, ascending, descending, keyboard].into_iter().max().unwrap_or(1)
+}
+
+fn run_length(chars: &[char], continues: impl Fn(char, char) -> bool) -> usize {
+    if chars.is_empty() {
+        return 0;
+    }
+    1 + chars
+        .windows(2)
+        .take_while(|pair| continues(pair[0], pair[1]))
+        .count()
+}
+
+// 1900 to 2099, when not part of a longer number
+fn is_recent_year(chars: &[char]) -> bool {
+    let year: String = chars.iter().take(YEAR_LENGTH).collect();
+    year.len() == YEAR_LENGTH
+        && chars.get(YEAR_LENGTH).is_none_or(|c| !c.is_ascii_digit())
+        && matches!(year.parse::<u32>(), Ok(1900..=2099))
+}
+
+fn is_keyboard_neighbour(previous: char, current: char) -> bool {
+    KEYBOARD_ROWS.iter().any(|row| {
+        let row: Vec<char> = row.chars().collect();
+        row.windows(2)
+            .any(|pair| (pair[0] == previous && pair[1] == current) || (pair[1] == previous && pair[0] == current))
+    })
+}
+
+fn character_set_size(chars: &[char]) -> usize {
+    let mut size = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        size += 10;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        size += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        size += 100;
+    }
+    size
+}
+
+// Undoes the usual character substitutions, `p@ssw0rd` is still `password`
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_passwords_with_a_digit_are_weak() {
+        assert!(estimate_score("password1", &[]) <= 1);
+        assert!(estimate_score("P@ssw0rd1", &[]) <= 1);
+    }
+
+    #[test]
+    fn keyboard_walks_and_sequences_are_weak() {
+        assert!(estimate_score("qwerty123", &[]) <= 1);
+        assert!(estimate_score("abcdefgh1", &[]) <= 1);
+        assert!(estimate_score("aaaaaaaaaa", &[]) <= 1);
+    }
+
+    #[test]
+    fn common_word_with_a_year_is_weak() {
+        assert!(estimate_score("Summer2024", &[]) <= 1);
+    }
+
+    #[test]
+    fn uncommon_word_with_substitutions_and_symbols_is_accepted() {
+        // Not in the word list, so only its length and character mix count
+        assert!(estimate_score("Tr0ub4dor&3", &[]) >= 2);
+    }
+
+    #[test]
+    fn long_passphrase_is_strong() {
+        assert_eq!(estimate_score("correct horse battery staple", &[]), MAX_SCORE);
+    }
+
+    #[test]
+    fn user_inputs_count_as_dictionary_words() {
+        let without_inputs = estimate_score("Marguerite2024", &[]);
+        let with_inputs = estimate_score("Marguerite2024", &["marguerite", "marguerite@example.com"]);
+        assert!(with_inputs < without_inputs);
+        assert!(with_inputs <= 1);
+    }
+
+    #[test]
+    fn empty_password_scores_zero() {
+        assert_eq!(estimate_score("", &[]), 0);
+    }
+}
@@ -0,0 +1,104 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand_core::{OsRng, RngCore};
+use totp_rs::{Algorithm, TOTP};
+
+use crate::config::config;
+
+const SECRET_LENGTH: usize = 20;
+const NONCE_LENGTH: usize = 12;
+const DIGITS: usize = 6;
+const STEP_SECONDS: u64 = 30;
+// Steps accepted on each side of the current one to absorb clock drift
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+// Unambiguous characters only, 32 of them so that `byte % 32` stays uniform
+const RECOVERY_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_LENGTH];
+    OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+fn build_totp(secret: Vec<u8>, issuer: &str, account_name: &str) -> TOTP {
+    // `:` separates issuer and account in the otpauth label
+    TOTP::new_unchecked(
+        Algorithm::SHA1,
+        DIGITS,
+        0,
+        STEP_SECONDS,
+        secret,
+        Some(issuer.replace(':', "")),
+        account_name.replace(':', ""),
+    )
+}
+
+// Returns the base32 secret for manual entry and the otpauth:// URI for QR codes
+pub async fn provisioning(secret: &[u8], account_name: &str) -> (String, String) {
+    let totp = build_totp(secret.to_vec(), config().await.mfa_issuer(), account_name);
+    (totp.get_secret_base32(), totp.get_url())
+}
+
+// Returns the time step the code belongs to, so callers can refuse to accept it twice
+pub fn verify_code(secret: &[u8], code: &str, unix_time: i64) -> Option<i64> {
+    let totp = build_totp(secret.to_vec(), "", "");
+    let current_step = unix_time / STEP_SECONDS as i64;
+
+    (current_step - ALLOWED_DRIFT_STEPS..=current_step + ALLOWED_DRIFT_STEPS)
+        .filter(|step| *step >= 0)
+        .find(|step| totp.check(code.trim(), *step as u64 * STEP_SECONDS))
+}
+
+// Stored as hex(nonce || ciphertext)
+pub async fn encrypt_secret(secret: &[u8]) -> Result<String, aes_gcm::Error> {
+    let cipher = Aes256Gcm::new(config().await.mfa_encryption_key().into());
+
+    let mut nonce = [0u8; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut encrypted = nonce.to_vec();
+    encrypted.extend(cipher.encrypt(Nonce::from_slice(&nonce), secret)?);
+    Ok(hex::encode(encrypted))
+}
+
+pub async fn decrypt_secret(encrypted: &str) -> Option<Vec<u8>> {
+    let cipher = Aes256Gcm::new(config().await.mfa_encryption_key().into());
+
+    let encrypted = hex::decode(encrypted).ok()?;
+    if encrypted.len() <= NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+    cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+// Codes look like `ABCDE-FGH23`
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 10];
+            OsRng.fill_bytes(&mut bytes);
+            let chars: String = bytes
+                .iter()
+                .map(|byte| RECOVERY_CODE_ALPHABET[(*byte as usize) % RECOVERY_CODE_ALPHABET.len()] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+// Recovery codes are compared without separators and case-insensitively
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+// TOTP codes are six digits, anything else is treated as a recovery code
+pub fn is_totp_code(code: &str) -> bool {
+    let code = code.trim();
+    code.len() == DIGITS && code.chars().all(|c| c.is_ascii_digit())
+}
@@ -0,0 +1,7 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+// Letters, digits and `_.-`, which keeps usernames safe to embed in URLs and otpauth labels
+pub static USERNAME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9_.-]+$").expect("username regex is valid"));